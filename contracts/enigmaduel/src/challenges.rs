@@ -0,0 +1,174 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    Timestamp, Uint128,
+};
+use cw_storage_plus::Bound;
+
+use crate::contract::execute::{
+    ensure_valid_stake, lock_stake, room_deadline, save_new_game_room, unlock_stake,
+};
+use crate::error::ContractError;
+use crate::fees::load_fee_config;
+use crate::msg::{
+    ChallengeResp, CreateChallengeResp, CreateGameRoomResp, GameRoomStatus, ListChallengesResp,
+};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::state::{Challenge, GameRoomsState, CHALLENGES, CHALLENGES_COUNT};
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn execute_create_challenge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stake: Uint128,
+    opponent: Option<String>,
+    expires: Timestamp,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    if env.block.time >= expires {
+        return Err(ContractError::ChallengeExpired {});
+    }
+    if opponent.as_deref() == Some(info.sender.as_str()) {
+        return Err(ContractError::OwnChallenge {});
+    }
+    ensure_valid_stake(deps.storage, stake)?;
+    lock_stake(deps.storage, &info.sender, stake)?;
+
+    let challenge_id = CHALLENGES_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    CHALLENGES_COUNT.save(deps.storage, &challenge_id)?;
+    CHALLENGES.save(
+        deps.storage,
+        challenge_id,
+        &Challenge {
+            challenger: info.sender.to_string(),
+            opponent: opponent.clone(),
+            stake,
+            expires,
+            created_at: env.block.time,
+        },
+    )?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&CreateChallengeResp(challenge_id))?)
+        .add_attributes(vec![
+            attr("action", "create_challenge"),
+            attr("challenge_id", challenge_id.to_string()),
+            attr("challenger", info.sender),
+            attr("opponent", opponent.unwrap_or_else(|| "anyone".to_string())),
+            attr("stake", stake),
+        ]))
+}
+
+pub fn execute_accept_challenge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    challenge_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+    if env.block.time >= challenge.expires {
+        return Err(ContractError::ChallengeExpired {});
+    }
+    if challenge.challenger == info.sender {
+        return Err(ContractError::OwnChallenge {});
+    }
+    if let Some(opponent) = &challenge.opponent {
+        if *opponent != info.sender {
+            return Err(ContractError::NotChallengeOpponent {});
+        }
+    }
+    // the room takes the fee schedule of today, the stake must still cover it
+    ensure_valid_stake(deps.storage, challenge.stake)?;
+    lock_stake(deps.storage, &info.sender, challenge.stake)?;
+    CHALLENGES.remove(deps.storage, challenge_id);
+
+    // both stakes are locked, the room starts right away
+    let game_room = GameRoomsState {
+        contestant1: challenge.challenger.clone(),
+        contestant2: info.sender.to_string(),
+        prize_pool: challenge.stake + challenge.stake,
+        status: GameRoomStatus::Started {},
+        created_at: env.block.time,
+        created_height: env.block.height,
+        deadline: room_deadline(deps.storage, &env.block)?,
+        secret_hash: None,
+        extra_contestants: vec![],
+        payout_table: vec![],
+        series: None,
+        stakes: vec![],
+        payout_policy: Default::default(),
+        teams: vec![],
+        fee_config: Some(load_fee_config(deps.storage, None)?),
+    };
+    let game_room_id = save_new_game_room(deps.storage, &game_room)?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&CreateGameRoomResp(game_room_id))?)
+        .add_attributes(vec![
+            attr("action", "accept_challenge"),
+            attr("challenge_id", challenge_id.to_string()),
+            attr("challenger", challenge.challenger),
+            attr("opponent", info.sender),
+            attr("room_id", game_room_id.to_string()),
+        ]))
+}
+
+// the challenger can cancel at any time, anyone else once the challenge expired.
+pub fn execute_cancel_challenge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    challenge_id: u64,
+) -> Result<Response, ContractError> {
+    let challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+    if challenge.challenger != info.sender && env.block.time < challenge.expires {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    CHALLENGES.remove(deps.storage, challenge_id);
+    unlock_stake(
+        deps.storage,
+        &Addr::unchecked(&challenge.challenger),
+        challenge.stake,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_challenge"),
+        attr("challenge_id", challenge_id.to_string()),
+        attr("challenger", challenge.challenger),
+        attr("stake", challenge.stake),
+    ]))
+}
+
+pub fn query_challenge(deps: Deps, challenge_id: u64) -> StdResult<Challenge> {
+    CHALLENGES.load(deps.storage, challenge_id)
+}
+
+// the challenges waiting for an opponent, the oldest first
+pub fn query_challenges(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListChallengesResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let challenges = CHALLENGES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(challenge_id, challenge)| ChallengeResp {
+                challenge_id,
+                challenge,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListChallengesResp { challenges })
+}
@@ -0,0 +1,166 @@
+use cosmwasm_std::{
+    attr, to_json_vec, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+    StdResult, Timestamp,
+};
+use sha2::{Digest, Sha256};
+
+use crate::contract::execute::{index_player_rooms, start_game_room};
+use crate::error::ContractError;
+use crate::helpers::cal_stake;
+use crate::invites::verify_room_secret;
+use crate::msg::{ConsentPayload, GameRoomStatus, GetConsentKeyResp, RoomConsents};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::state::{game_rooms_state, GameRoomsState, CONSENT_KEYS, USED_CONSENTS, USED_ROOM_KEYS};
+
+pub fn execute_join_room(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    game_room_id: u64,
+    secret: Option<Binary>,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let mut game_room = game_rooms_state().load(deps.storage, game_room_id)?;
+
+    if env.block.time >= game_room.deadline {
+        return Err(ContractError::GameRoomExpired {});
+    }
+
+    let mut joined = match game_room.status.clone() {
+        GameRoomStatus::Pending { joined } => joined,
+        _ => return Err(ContractError::GameRoomNotPending {}),
+    };
+
+    let sender = info.sender.to_string();
+    if let Some(secret_hash) = &game_room.secret_hash {
+        verify_room_secret(secret_hash, secret)?;
+        // the first holder of the secret takes the free seat
+        if game_room.contestant2.is_empty() && sender != game_room.contestant1 {
+            game_room.contestant2 = sender.clone();
+            index_player_rooms(deps.storage, game_room_id, &game_room)?;
+        }
+    }
+    if !game_room.contestants().contains(&&sender) {
+        return Err(ContractError::NotAContestant {});
+    }
+    if !joined.contains(&sender) {
+        joined.push(sender.clone());
+    }
+
+    // the last consent locks the stakes of every contestant
+    let all_joined = joined.len() == game_room.contestants().len();
+    game_room.status = GameRoomStatus::Pending { joined };
+    if all_joined {
+        start_game_room(deps.storage, &env.block, &mut game_room)?;
+    }
+
+    game_rooms_state().save(deps.storage, game_room_id, &game_room)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "join_room"),
+        attr("room_id", game_room_id.to_string()),
+        attr("contestant", sender),
+        attr("status", game_room.status.to_string()),
+    ]))
+}
+
+pub fn execute_register_consent_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Binary,
+) -> Result<Response, ContractError> {
+    // compressed or uncompressed secp256k1 public key
+    if pubkey.len() != 33 && pubkey.len() != 65 {
+        return Err(ContractError::Std(StdError::generic_err(
+            "invalid secp256k1 public key",
+        )));
+    }
+    CONSENT_KEYS.save(deps.storage, &info.sender, &pubkey)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_consent_key"),
+        attr("user", info.sender),
+    ]))
+}
+
+/// the payload both contestants sign to agree to the stake and the terms of a room.
+pub fn consent_payload(
+    contract: &Addr,
+    game_room: &GameRoomsState,
+    room_key: &str,
+    expires: Timestamp,
+) -> ConsentPayload {
+    ConsentPayload {
+        contract: contract.to_string(),
+        contestant1: game_room.contestant1.clone(),
+        contestant2: game_room.contestant2.clone(),
+        stake: cal_stake(game_room.prize_pool),
+        payout_policy: game_room.payout_policy.clone(),
+        fee_config: game_room.fee_config.clone(),
+        room_key: room_key.to_string(),
+        expires,
+    }
+}
+
+/// the hash the contestants sign.
+pub fn consent_hash(payload: &ConsentPayload) -> StdResult<Vec<u8>> {
+    Ok(Sha256::digest(to_json_vec(payload)?).to_vec())
+}
+
+// checks the off-chain consents of both contestants and consumes them
+pub fn verify_consents(
+    deps: DepsMut,
+    env: &Env,
+    game_room: &GameRoomsState,
+    consents: RoomConsents,
+) -> Result<(), ContractError> {
+    if env.block.time >= consents.expires {
+        return Err(ContractError::ConsentExpired {});
+    }
+
+    let hash = consent_hash(&consent_payload(
+        &env.contract.address,
+        game_room,
+        &consents.room_key,
+        consents.expires,
+    ))?;
+
+    // every room key starts a single room, whatever the consents signed for it
+    if USED_CONSENTS.has(deps.storage, &hash)
+        || USED_ROOM_KEYS.has(deps.storage, &consents.room_key)
+    {
+        return Err(ContractError::ConsentAlreadyUsed {});
+    }
+
+    for (contestant, signature) in [
+        (&game_room.contestant1, &consents.contestant1_signature),
+        (&game_room.contestant2, &consents.contestant2_signature),
+    ] {
+        let pubkey = CONSENT_KEYS
+            .may_load(deps.storage, &Addr::unchecked(contestant))?
+            .ok_or(ContractError::ConsentKeyNotRegistered {
+                user: contestant.clone(),
+            })?;
+
+        if !deps
+            .api
+            .secp256k1_verify(&hash, signature, &pubkey)
+            .unwrap_or(false)
+        {
+            return Err(ContractError::InvalidConsentSignature {
+                user: contestant.clone(),
+            });
+        }
+    }
+
+    USED_CONSENTS.save(deps.storage, &hash, &Empty {})?;
+    USED_ROOM_KEYS.save(deps.storage, &consents.room_key, &Empty {})?;
+    Ok(())
+}
+
+pub fn query_consent_key(deps: Deps, user: String) -> StdResult<GetConsentKeyResp> {
+    Ok(GetConsentKeyResp {
+        pubkey: CONSENT_KEYS.may_load(deps.storage, &Addr::unchecked(user))?,
+    })
+}
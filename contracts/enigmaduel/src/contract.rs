@@ -1,45 +1,107 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
-    Uint256,
+    to_json_binary, Addr, Binary, BlockInfo, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+    StdResult, Storage, Timestamp, Uint128, Uint256,
 };
-use cw2::set_contract_version;
+use cw2::{ensure_from_older_version, set_contract_version};
 use execute::*;
 
+use crate::challenges::{
+    execute_accept_challenge, execute_cancel_challenge, execute_create_challenge, query_challenge,
+    query_challenges,
+};
+use crate::consent::{
+    execute_join_room, execute_register_consent_key, query_consent_key, verify_consents,
+};
+use crate::enumerable::{
+    query_active_rooms, query_game_rooms, query_rooms_by_pair, query_rooms_by_player,
+};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, GameRoomStatus, InstantiateMsg, QueryMsg};
+use crate::fees::{
+    credit_fees, ensure_valid_fee_config, execute_burn_fees, execute_set_referrer,
+    execute_update_config, execute_update_fee_recipients, load_fee_config, query_config,
+    query_fee_recipients, query_referrer, query_simulate_room,
+};
+use crate::handicap::{execute_create_handicap_room, settle_handicap_room};
+use crate::invites::execute_create_private_room;
+use crate::leaderboard::query_leaderboard;
+use crate::leagues::{
+    execute_cancel_league, execute_create_league, execute_end_league_season,
+    execute_register_for_league, execute_start_league_season, finish_league_fixture, query_league,
+    query_league_fixtures, query_league_standings, query_leagues,
+};
+use crate::matchmaking::{execute_join_queue, execute_leave_queue, query_queue, query_queue_entry};
+use crate::msg::{
+    ExecuteMsg, GameRoomStatus, GetAdminResp, GetRoomTimeoutResp, InstantiateMsg, MigrateMsg,
+    QueryMsg, Role,
+};
+use crate::pause::{
+    ensure_not_paused, execute_set_pause_state, execute_withdraw_only, query_pause_state, Operation,
+};
+use crate::ranked::{execute_create_ranked_room, settle_ranked_room};
+use crate::ratings::{
+    execute_update_k_factor, query_k_factor, query_rating, query_rating_history, update_ratings,
+};
+use crate::roles::{
+    ensure_admin, ensure_role, execute_grant_role, execute_revoke_role, query_role_members,
+    query_user_roles, transfer_roles,
+};
+use crate::scoring::{ensure_valid_payout_policy, ensure_valid_scores, settle_scored_room};
+use crate::series::{execute_create_series_room, execute_report_series_game, query_series_score};
 use crate::state::{
-    Balance, GameRoomsState, ADMIN, BALANCES, ENIGMA_DUEL_TOKEN, FEE, GAME_ROOMS_COUNT,
-    GAME_ROOMS_STATE,
+    game_rooms_state, Balance, FeeConfig, FeeLedger, GameRoomsState, ADMIN, BALANCES,
+    ENIGMA_DUEL_TOKEN, FEE_ACCRUED, FEE_CONFIG, FEE_LEDGER, FEE_RECIPIENTS, GAME_ROOMS_COUNT,
+    LEAGUE_ROOMS, PENDING_ADMIN, PLAYER_ROOMS, ROLES, ROOM_TIMEOUT, TOURNAMENT_ROOMS,
+};
+use crate::stats::{query_player_history, query_player_stats, record_match};
+use crate::teams::{execute_create_team_room, settle_team_room};
+use crate::tournaments::{
+    execute_cancel_tournament, execute_create_tournament, execute_register_for_tournament,
+    execute_start_tournament, finish_tournament_match, query_tournament, query_tournament_bracket,
+    query_tournaments,
 };
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:enigmaduel";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// an hour for the game servers to settle a room
+pub const DEFAULT_ROOM_TIMEOUT: u64 = 3600;
+// a room expiring as soon as it starts would refund every game before it's settled
+pub const MIN_ROOM_TIMEOUT: u64 = 60;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    info: MessageInfo,
+    _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     // setting the contract version
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    // setting the fee for each game result
-    FEE.save(deps.storage, &msg.fee)?;
+    // a flat fee for each contestant of a decided room, the schedule can be changed later
+    FEE_CONFIG.save(deps.storage, &FeeConfig::flat(msg.fee))?;
 
-    // setting the admin address that collects the fees as well.
+    let room_timeout = msg.room_timeout.unwrap_or(DEFAULT_ROOM_TIMEOUT);
+    execute::ensure_valid_room_timeout(room_timeout)?;
+    ROOM_TIMEOUT.save(deps.storage, &room_timeout)?;
+
+    // setting the admin address.
     ADMIN.save(deps.storage, &Addr::unchecked(msg.admin.clone()))?;
 
-    // instantiating the admin address as the fee collector.
-    BALANCES.save(
-        deps.storage,
-        &Addr::unchecked(msg.admin.clone()),
-        &Balance::new_zero(),
-    )?;
+    // the admin starts with every role, the narrower keys can be granted afterwards.
+    for role in Role::all() {
+        ROLES.save(
+            deps.storage,
+            (role.key(), &Addr::unchecked(msg.admin.clone())),
+            &Empty {},
+        )?;
+    }
+
+    // the fees are kept in their own ledger, apart from the balance of the admin.
+    FEE_LEDGER.save(deps.storage, &FeeLedger::default())?;
 
     // instantiating the enigma duel token address.
     ENIGMA_DUEL_TOKEN.save(deps.storage, &(Addr::unchecked(msg.enigma_token_duel)))?;
@@ -63,31 +125,149 @@ pub fn execute(
         }
         ExecuteMsg::CreateGameRoom {
             game_room_init_params,
-        } => create_game_room(deps, info, game_room_init_params),
+        } => create_game_room(deps, _env, info, game_room_init_params),
         ExecuteMsg::FinishGameRoom {
             game_room_finish_params,
-        } => finish_game_room(deps, info, game_room_finish_params),
+        } => finish_game_room(deps, _env, info, game_room_finish_params),
         ExecuteMsg::CollectFees {
             collect_fees_params,
         } => collect_fees(deps, info, collect_fees_params),
         ExecuteMsg::Receive(receive_msg) => {
             execute::update_balance_callback(deps, info, receive_msg.msg)
         }
+        ExecuteMsg::ProposeAdmin { new_admin } => propose_admin(deps, info, new_admin),
+        ExecuteMsg::AcceptAdmin {} => accept_admin(deps, info),
+        ExecuteMsg::RenounceAdmin {} => renounce_admin(deps, info),
+        ExecuteMsg::GrantRole { role, addr } => execute_grant_role(deps, info, role, addr),
+        ExecuteMsg::RevokeRole { role, addr } => execute_revoke_role(deps, info, role, addr),
+        ExecuteMsg::SetPauseState { pause_state } => {
+            execute_set_pause_state(deps, info, pause_state)
+        }
+        ExecuteMsg::WithdrawOnly {} => execute_withdraw_only(deps, info),
+        ExecuteMsg::JoinRoom {
+            game_room_id,
+            secret,
+        } => execute_join_room(deps, _env, info, game_room_id, secret),
+        ExecuteMsg::CreateRankedRoom {
+            contestants,
+            stake,
+            payout_table,
+        } => execute_create_ranked_room(deps, _env, info, contestants, stake, payout_table),
+        ExecuteMsg::CreateTournament {
+            name,
+            entry_fee,
+            max_players,
+            payout_table,
+        } => {
+            execute_create_tournament(deps, _env, info, name, entry_fee, max_players, payout_table)
+        }
+        ExecuteMsg::RegisterForTournament { tournament_id } => {
+            execute_register_for_tournament(deps, _env, info, tournament_id)
+        }
+        ExecuteMsg::StartTournament { tournament_id } => {
+            execute_start_tournament(deps, _env, info, tournament_id)
+        }
+        ExecuteMsg::CancelTournament { tournament_id } => {
+            execute_cancel_tournament(deps, _env, info, tournament_id)
+        }
+        ExecuteMsg::CreateHandicapRoom {
+            contestant1,
+            contestant2,
+            stake1,
+            stake2,
+        } => {
+            execute_create_handicap_room(deps, _env, info, contestant1, contestant2, stake1, stake2)
+        }
+        ExecuteMsg::CreateTeamRoom { team1, team2 } => {
+            execute_create_team_room(deps, _env, info, team1, team2)
+        }
+        ExecuteMsg::CreateSeriesRoom {
+            contestant1,
+            contestant2,
+            stake,
+            best_of,
+        } => execute_create_series_room(deps, _env, info, contestant1, contestant2, stake, best_of),
+        ExecuteMsg::ReportSeriesGame {
+            game_room_id,
+            result,
+        } => execute_report_series_game(deps, _env, info, game_room_id, result),
+        ExecuteMsg::CreateLeague {
+            name,
+            entry_fee,
+            divisions,
+            division_size,
+            movers,
+            payout_table,
+        } => execute_create_league(
+            deps,
+            _env,
+            info,
+            name,
+            entry_fee,
+            divisions,
+            division_size,
+            movers,
+            payout_table,
+        ),
+        ExecuteMsg::RegisterForLeague { league_id } => {
+            execute_register_for_league(deps, _env, info, league_id)
+        }
+        ExecuteMsg::StartLeagueSeason { league_id } => {
+            execute_start_league_season(deps, _env, info, league_id)
+        }
+        ExecuteMsg::EndLeagueSeason { league_id } => {
+            execute_end_league_season(deps, _env, info, league_id)
+        }
+        ExecuteMsg::CancelLeague { league_id } => {
+            execute_cancel_league(deps, _env, info, league_id)
+        }
+        ExecuteMsg::CreatePrivateRoom { stake, secret_hash } => {
+            execute_create_private_room(deps, _env, info, stake, secret_hash)
+        }
+        ExecuteMsg::RegisterConsentKey { pubkey } => {
+            execute_register_consent_key(deps, info, pubkey)
+        }
+        ExecuteMsg::ExpireGameRoom { game_room_id } => expire_game_room(deps, _env, game_room_id),
+        ExecuteMsg::UpdateRoomTimeout { room_timeout } => {
+            update_room_timeout(deps, info, room_timeout)
+        }
+        ExecuteMsg::UpdateConfig { fee_config } => execute_update_config(deps, info, fee_config),
+        ExecuteMsg::UpdateFeeRecipients { recipients } => {
+            execute_update_fee_recipients(deps, info, recipients)
+        }
+        ExecuteMsg::BurnFees {} => execute_burn_fees(deps, info),
+        ExecuteMsg::SetReferrer { referrer } => execute_set_referrer(deps, info, referrer),
+        ExecuteMsg::UpdateEloKFactor { k_factor } => execute_update_k_factor(deps, info, k_factor),
+        ExecuteMsg::JoinQueue {
+            stake,
+            max_rating_gap,
+        } => execute_join_queue(deps, _env, info, stake, max_rating_gap),
+        ExecuteMsg::LeaveQueue {} => execute_leave_queue(deps, info),
+        ExecuteMsg::CreateChallenge {
+            stake,
+            opponent,
+            expires,
+        } => execute_create_challenge(deps, _env, info, stake, opponent, expires),
+        ExecuteMsg::AcceptChallenge { challenge_id } => {
+            execute_accept_challenge(deps, _env, info, challenge_id)
+        }
+        ExecuteMsg::CancelChallenge { challenge_id } => {
+            execute_cancel_challenge(deps, _env, info, challenge_id)
+        }
     }
 }
 
 pub mod execute {
-    use cosmwasm_std::{from_json, Uint256};
+    use cosmwasm_std::from_json;
 
     use super::*;
     use crate::{
         error::{self, InsufficientBalanceErr},
-        helpers::{cal_min_required, create_key_hash},
+        helpers::{cal_stake, duel_winner, even_duel_payouts},
         msg::{
-            CollectFeesParams, GameRoomFinishParams, GameRoomIntiParams,
+            CollectFeesParams, CreateGameRoomResp, GameRoomFinishParams, GameRoomIntiParams,
             UpdateBalanceMode::{self, *},
         },
-        state::GAME_ROOMS_COUNT,
     };
 
     // creating a proper response for each function
@@ -99,7 +279,14 @@ pub mod execute {
     ) -> Result<Response, ContractError> {
         // address doesn't need be validated because the internal state is not getting changed,
         // in the call back we change the balance and we are sure that the address is correct.
-        // Your contract logic here
+        ensure_not_paused(
+            deps.storage,
+            match update_mode {
+                Deposit { .. } => Operation::Deposits,
+                Withdraw { .. } => Operation::Withdrawals,
+            },
+        )?;
+
         Ok(Response::new()
             .add_attribute("action", "update_balance_request")
             .add_attribute("request_data", update_mode.to_string())
@@ -173,6 +360,7 @@ pub mod execute {
         let update_balance_data: UpdateBalanceMode =
             match from_json::<UpdateBalanceMode>(&update_mode)? {
                 Deposit { amount, user } => {
+                    ensure_not_paused(deps.storage, Operation::Deposits)?;
                     BALANCES.update(
                         deps.storage,
                         &Addr::unchecked(user.clone().unwrap()),
@@ -189,6 +377,7 @@ pub mod execute {
                     user,
                     receiver,
                 } => {
+                    ensure_not_paused(deps.storage, Operation::Withdrawals)?;
                     BALANCES.update(
                         deps.storage,
                         &Addr::unchecked(user.clone().unwrap()),
@@ -209,228 +398,447 @@ pub mod execute {
     }
 
     pub fn create_game_room(
-        deps: DepsMut,
+        mut deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         params: GameRoomIntiParams,
     ) -> Result<Response, ContractError> {
-        // sender must be app admin
-        if info.sender != ADMIN.load(deps.storage)? {
-            return Err(error::ContractError::Unauthorized {});
-        }
-
-        // each contestant must have whole prize pool amount - enigma duel fee / 2 token balances
-        // loading the fee
-        let min_required = cal_min_required(params.prize_pool, Uint128::zero());
+        // sender must be a game operator
+        ensure_role(deps.as_ref(), &info, Role::Operator)?;
+        ensure_not_paused(deps.storage, Operation::RoomCreation)?;
 
-        let (con_1_bal, con_2_bal) = (
-            BALANCES
-                .may_load(deps.storage, &Addr::unchecked(params.contestant1.clone()))
-                .unwrap()
-                .unwrap(),
-            BALANCES
-                .may_load(deps.storage, &Addr::unchecked(params.contestant2.clone()))
-                .unwrap()
-                .unwrap(),
-        );
-        println!("got the balances, {:?}{:?}", con_1_bal, con_2_bal);
-
-        // in the following line we also check the prize pool to not be zero
-        let con_1_av = con_1_bal.available_balance();
-        let con_2_av = con_2_bal.available_balance();
-        if min_required >= con_1_av || min_required >= con_2_av {
-            return Err(error::ContractError::InsufficientBalance(
-                InsufficientBalanceErr {
-                    min_required,
-                    current_balance: con_1_av,
-                    user: params.contestant1.clone(),
-                },
-            ));
-        } else if min_required >= con_2_av {
-            return Err(error::ContractError::InsufficientBalance(
-                InsufficientBalanceErr {
-                    min_required,
-                    current_balance: con_2_av,
-                    user: params.contestant2.clone(),
-                },
-            ));
+        let payout_policy = params.payout_policy.unwrap_or_default();
+        ensure_valid_payout_policy(&payout_policy)?;
+        // only the admin sets a room its own fee schedule
+        if let Some(fee_config) = &params.fee_config {
+            ensure_admin(deps.as_ref(), &info)?;
+            ensure_valid_fee_config(fee_config)?;
+        }
+        // the room keeps the schedule it was created with and the contestants consent to it,
+        // the stake must cover the fees of both contestants with it
+        let fee_config = match params.fee_config {
+            Some(fee_config) => fee_config,
+            None => load_fee_config(deps.storage, None)?,
+        };
+        let stake = cal_stake(params.prize_pool);
+        if stake < fee_config.fee(stake) * Uint128::new(2) {
+            return Err(error::ContractError::InvalidStake {});
         }
-        println!("got the balances");
 
-        // creating the key of the these two components as the key
-        let game_room_key = create_key_hash(params.contestant1.clone(), params.contestant2.clone());
-        let game_room_data = GameRoomsState {
+        // nothing is locked until both of the contestants agreed to the stake
+        let mut game_room_data = GameRoomsState {
             contestant1: params.contestant1.clone(),
             contestant2: params.contestant2.clone(),
             prize_pool: params.prize_pool,
-            status: GameRoomStatus::Started {},
+            status: GameRoomStatus::Pending { joined: vec![] },
+            created_at: env.block.time,
+            created_height: env.block.height,
+            deadline: room_deadline(deps.storage, &env.block)?,
+            secret_hash: None,
+            extra_contestants: vec![],
+            payout_table: vec![],
+            series: None,
+            stakes: vec![],
+            payout_policy,
+            teams: vec![],
+            fee_config: Some(fee_config),
         };
-        println!("got the balances");
-
-        // checking the previous existence
-        match GAME_ROOMS_STATE.may_load(deps.storage, game_room_key.clone()) {
-            // at this point the game room was initialized previously, we check that the game room must have been finished previously
-            Ok(option_state) => match option_state {
-                Some(state) => match state.status {
-                    GameRoomStatus::Started {} => {
-                        return Err(error::ContractError::GameRoomAlreadyStarted {});
-                    }
 
-                    _ =>
-                    // exists before, updating
-                    {
-                        GAME_ROOMS_STATE.update(
-                            deps.storage,
-                            game_room_key.clone(),
-                            |state: Option<GameRoomsState>| -> Result<GameRoomsState, ContractError> {
-                                match state {
-                                    Some(_) => Ok(game_room_data.clone()),
-                                    None => Err(error::ContractError::GameRoomLoadError { msg: "couldn't load existing room !".to_string() }),
-                                }
-                            },
-                        )?;
-                    }
+        // the signed consents start the room right away
+        if let Some(consents) = params.consents {
+            verify_consents(deps.branch(), &env, &game_room_data, consents)?;
+            start_game_room(deps.storage, &env.block, &mut game_room_data)?;
+        }
+
+        let game_room_id = save_new_game_room(deps.storage, &game_room_data)?;
+
+        Ok(Response::new()
+            .set_data(to_json_binary(&CreateGameRoomResp(game_room_id))?)
+            .add_attribute("action", "crate_game_room")
+            .add_attribute("room_id", game_room_id.to_string())
+            .add_attribute("status", game_room_data.status.to_string()))
+    }
+
+    // every room gets a fresh id, a pair can play any number of rooms at once
+    pub fn save_new_game_room(
+        storage: &mut dyn Storage,
+        game_room: &GameRoomsState,
+    ) -> StdResult<u64> {
+        let game_room_id = GAME_ROOMS_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+        GAME_ROOMS_COUNT.save(storage, &game_room_id)?;
+        game_rooms_state().save(storage, game_room_id, game_room)?;
+        index_player_rooms(storage, game_room_id, game_room)?;
+        Ok(game_room_id)
+    }
+
+    // the seat of an invite room stays empty until the holder of the secret joins
+    pub fn index_player_rooms(
+        storage: &mut dyn Storage,
+        game_room_id: u64,
+        game_room: &GameRoomsState,
+    ) -> StdResult<()> {
+        for contestant in game_room.contestants() {
+            if !contestant.is_empty() {
+                PLAYER_ROOMS.save(storage, (contestant, game_room_id), &Empty {})?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn ensure_valid_room_timeout(room_timeout: u64) -> Result<(), ContractError> {
+        if room_timeout < MIN_ROOM_TIMEOUT {
+            return Err(ContractError::InvalidRoomTimeout {
+                min: MIN_ROOM_TIMEOUT,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn room_deadline(storage: &dyn Storage, block: &BlockInfo) -> StdResult<Timestamp> {
+        let room_timeout = ROOM_TIMEOUT
+            .may_load(storage)?
+            .unwrap_or(DEFAULT_ROOM_TIMEOUT);
+        Ok(block.time.plus_seconds(room_timeout))
+    }
+
+    // locks the stake of every contestant and marks the room as started
+    pub fn start_game_room(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        game_room: &mut GameRoomsState,
+    ) -> Result<(), ContractError> {
+        // each contestant must have their share of the prize pool
+        let stakes = game_room.stakes();
+
+        for (contestant, min_required) in game_room.contestants().into_iter().zip(&stakes) {
+            let available = BALANCES
+                .may_load(storage, &Addr::unchecked(contestant))?
+                .unwrap_or_default()
+                .available_balance();
+
+            // in the following line we also check the prize pool to not be zero
+            if *min_required >= available {
+                return Err(error::ContractError::InsufficientBalance(
+                    InsufficientBalanceErr {
+                        min_required: *min_required,
+                        current_balance: available,
+                        user: contestant.clone(),
+                    },
+                ));
+            }
+        }
+
+        // locking the prize pool amount form every contestant
+        for (contestant, stake) in game_room.contestants().into_iter().zip(stakes) {
+            BALANCES.update(
+                storage,
+                &Addr::unchecked(contestant),
+                |balance: Option<Balance>| -> StdResult<_> {
+                    Ok(balance.unwrap_or_default().lock(stake))
                 },
-                None => {
-                    // doesn't exits adding
-                    GAME_ROOMS_STATE.save(deps.storage, game_room_key.clone(), &game_room_data)?
-                }
-            },
-            Err(e) => return Err(error::ContractError::GameRoomLoadError { msg: e.to_string() }),
+            )?;
+        }
+
+        // the operator has a whole timeout to settle once the stakes are locked
+        game_room.status = GameRoomStatus::Started {};
+        game_room.deadline = room_deadline(storage, block)?;
+        Ok(())
+    }
+
+    // the winner is paid the stake of the loser minus the fees of both
+    pub fn ensure_valid_stake(storage: &dyn Storage, stake: Uint128) -> Result<(), ContractError> {
+        let fee = load_fee_config(storage, None)?.fee(stake);
+        if stake.is_zero() || stake < fee * Uint128::new(2) {
+            return Err(error::ContractError::InvalidStake {});
         }
+        Ok(())
+    }
 
-        // locking the prize pool amount form the both contestants
-        println!("got the balances");
+    // locks a stake waiting for an opponent
+    pub fn lock_stake(
+        storage: &mut dyn Storage,
+        player: &Addr,
+        stake: Uint128,
+    ) -> Result<(), ContractError> {
+        let available = BALANCES
+            .may_load(storage, player)?
+            .unwrap_or_default()
+            .available_balance();
+
+        if stake >= available {
+            return Err(error::ContractError::InsufficientBalance(
+                InsufficientBalanceErr {
+                    min_required: stake,
+                    current_balance: available,
+                    user: player.to_string(),
+                },
+            ));
+        }
 
-        // locking
         BALANCES.update(
-            deps.storage,
-            &Addr::unchecked(params.contestant1),
+            storage,
+            player,
             |balance: Option<Balance>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default().lock(min_required))
+                Ok(balance.unwrap_or_default().lock(stake))
             },
         )?;
+        Ok(())
+    }
 
-        // locking
+    // gives back a stake that never made it to a room
+    pub fn unlock_stake(storage: &mut dyn Storage, player: &Addr, stake: Uint128) -> StdResult<()> {
         BALANCES.update(
-            deps.storage,
-            &Addr::unchecked(params.contestant2),
+            storage,
+            player,
             |balance: Option<Balance>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default().lock(min_required))
+                Ok(balance
+                    .unwrap_or_default()
+                    .unlock_and_decrease(stake, Uint128::zero()))
             },
         )?;
+        Ok(())
+    }
+
+    pub fn expire_game_room(
+        deps: DepsMut,
+        env: Env,
+        game_room_id: u64,
+    ) -> Result<Response, ContractError> {
+        ensure_not_paused(deps.storage, Operation::RoomSettlement)?;
+
+        let game_room = game_rooms_state().load(deps.storage, game_room_id)?;
+
+        // refunding a match would leave the bracket without a winner to advance
+        if TOURNAMENT_ROOMS.has(deps.storage, game_room_id) {
+            return Err(error::ContractError::TournamentMatchCantExpire {});
+        }
+        // nor would the season end with a fixture never played
+        if LEAGUE_ROOMS.has(deps.storage, game_room_id) {
+            return Err(error::ContractError::LeagueFixtureCantExpire {});
+        }
+        if env.block.time < game_room.deadline {
+            return Err(error::ContractError::GameRoomDeadlineNotReached {});
+        }
+
+        match game_room.status {
+            // refunding every contestant
+            GameRoomStatus::Started {} => {
+                for (contestant, stake) in
+                    game_room.contestants().into_iter().zip(game_room.stakes())
+                {
+                    unlock_stake(deps.storage, &Addr::unchecked(contestant), stake)?;
+                }
+            }
+            // nothing was locked yet
+            GameRoomStatus::Pending { .. } => {}
+            _ => return Err(error::ContractError::GameRoomNotStarted {}),
+        }
+
+        game_rooms_state().save(
+            deps.storage,
+            game_room_id,
+            &game_room.get_finish_state(GameRoomStatus::Expired {}),
+        )?;
 
-        let count = &GAME_ROOMS_COUNT
-            .load(deps.storage)
-            .unwrap_or(Uint256::zero());
-        GAME_ROOMS_COUNT
-            .save(deps.storage, &count.checked_add(Uint256::one()).unwrap())
-            .unwrap();
         Ok(Response::new()
-            .add_attribute("action", "crate_game_room")
-            .add_attribute("room_key", game_room_key))
+            .add_attribute("action", "expire_game_room")
+            .add_attribute("room_id", game_room_id.to_string()))
+    }
+
+    pub fn update_room_timeout(
+        deps: DepsMut,
+        info: MessageInfo,
+        room_timeout: u64,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(deps.as_ref(), &info)?;
+        ensure_valid_room_timeout(room_timeout)?;
+
+        // applies to the rooms created or started from now on
+        ROOM_TIMEOUT.save(deps.storage, &room_timeout)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_room_timeout")
+            .add_attribute("room_timeout", room_timeout.to_string()))
     }
 
     pub fn finish_game_room(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         params: GameRoomFinishParams,
     ) -> Result<Response, ContractError> {
-        // sender must be app admin
-        if info.sender != ADMIN.load(deps.storage)? {
-            return Err(error::ContractError::Unauthorized {});
-        }
+        // sender must be a game operator
+        ensure_role(deps.as_ref(), &info, Role::Operator)?;
+        ensure_not_paused(deps.storage, Operation::RoomSettlement)?;
 
         // loading the game room info
-        let pre_game_room_state =
-            GAME_ROOMS_STATE.load(deps.storage, params.game_room_key.clone())?;
+        let pre_game_room_state = game_rooms_state().load(deps.storage, params.game_room_id)?;
+        if pre_game_room_state.status != (GameRoomStatus::Started {}) {
+            return Err(error::ContractError::GameRoomNotStarted {});
+        }
+        // the multi-player rooms only settle with a ranking and the duels never do
+        if pre_game_room_state.is_ranked() != matches!(params.result, GameRoomStatus::Ranked { .. })
+        {
+            return Err(error::ContractError::InvalidRoomResult {});
+        }
+        // the team rooms settle with the winning side or a draw
+        if pre_game_room_state.is_team() != matches!(params.result, GameRoomStatus::TeamWin { .. })
+            && params.result != (GameRoomStatus::Draw {})
+        {
+            return Err(error::ContractError::InvalidRoomResult {});
+        }
+        // every game of a series is reported on its own
+        if pre_game_room_state.series.is_some() {
+            return Err(error::ContractError::SeriesInProgress {});
+        }
 
-        // specifying the win or draw and changing the balances of the contestants - the platform fee
-        match params.result.clone() {
-            GameRoomStatus::Started {} => return Err(error::ContractError::GameRoomNotStarted {}),
-            GameRoomStatus::Win { addr } => {
-                // modifying the game room state
-                GAME_ROOMS_STATE.update(
-                    deps.storage,
-                    params.game_room_key.clone(),
-                    |_| -> StdResult<_> { Ok(pre_game_room_state.get_finish_state(params.result)) },
-                )?;
+        // the tournament rooms hold no stakes, the winner moves on in the bracket
+        if let Some(match_ref) = TOURNAMENT_ROOMS.may_load(deps.storage, params.game_room_id)? {
+            return finish_tournament_match(
+                deps.storage,
+                &env.block,
+                params.game_room_id,
+                &pre_game_room_state,
+                match_ref,
+                params.result,
+            );
+        }
+        // the league rooms hold no stakes either, the result goes to the points table
+        if let Some(fixture) = LEAGUE_ROOMS.may_load(deps.storage, params.game_room_id)? {
+            return finish_league_fixture(
+                deps.storage,
+                &env.block,
+                params.game_room_id,
+                &pre_game_room_state,
+                fixture,
+                params.result,
+            );
+        }
 
-                // increasing the winner balance
-                let tmp_fee = FEE.load(deps.storage)?;
-                BALANCES.update(
-                    deps.storage,
-                    &Addr::unchecked(addr.clone()),
-                    |balance: Option<Balance>| -> StdResult<_> {
-                        Ok(balance.unwrap_or_default().unlock_and_increase(
-                            cal_min_required(pre_game_room_state.prize_pool, Uint128::zero()),
-                            cal_min_required(pre_game_room_state.prize_pool, tmp_fee),
-                        ))
-                    },
-                )?;
+        settle_game_room(
+            deps.storage,
+            &env.block,
+            params.game_room_id,
+            pre_game_room_state,
+            params.result,
+        )
+    }
 
-                let loser = if pre_game_room_state.contestant1 == addr {
-                    pre_game_room_state.contestant2
-                } else {
-                    pre_game_room_state.contestant1
-                };
-
-                // decreasing the loser balance
-                BALANCES.update(
-                    deps.storage,
-                    &Addr::unchecked(loser),
-                    |balance: Option<Balance>| -> StdResult<_> {
-                        Ok(balance.unwrap_or_default().unlock_and_decrease(
-                            cal_min_required(pre_game_room_state.prize_pool, Uint128::zero()),
-                            cal_min_required(pre_game_room_state.prize_pool, Uint128::zero()),
-                        ))
-                    },
+    // moves the stakes of a started room to the winners and records the result
+    pub fn settle_game_room(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        game_room_id: u64,
+        pre_game_room_state: GameRoomsState,
+        result: GameRoomStatus,
+    ) -> Result<Response, ContractError> {
+        if let GameRoomStatus::Scored { scores } = &result {
+            ensure_valid_scores(&pre_game_room_state, scores)?;
+        }
+
+        let fee_config = load_fee_config(storage, Some(&pre_game_room_state))?;
+        // the winner of a duel must be one of its two contestants
+        let winner = match &result {
+            GameRoomStatus::Win { addr } => Some(duel_winner(&pre_game_room_state, addr)?),
+            _ => None,
+        };
+
+        // specifying the win or draw and changing the balances of the contestants - the platform fee
+        match result.clone() {
+            GameRoomStatus::Started {}
+            | GameRoomStatus::Pending { .. }
+            | GameRoomStatus::Expired {} => {
+                return Err(error::ContractError::GameRoomNotStarted {})
+            }
+            GameRoomStatus::Ranked { ranking } => {
+                settle_ranked_room(
+                    storage,
+                    game_room_id,
+                    &pre_game_room_state,
+                    ranking,
+                    &fee_config,
                 )?;
-                let admin_addr = ADMIN.load(deps.storage)?;
-
-                BALANCES.update(
-                    deps.storage,
-                    &admin_addr,
-                    |balance: Option<Balance>| -> StdResult<_> {
-                        Ok(balance
-                            .unwrap_or_default()
-                            .total_increase(tmp_fee.checked_add(tmp_fee).unwrap()))
-                    },
+            }
+            GameRoomStatus::Scored { scores } => {
+                settle_scored_room(
+                    storage,
+                    game_room_id,
+                    &pre_game_room_state,
+                    scores,
+                    &fee_config,
                 )?;
             }
-            GameRoomStatus::Draw {} => {
-                // modifying the game room state
-                GAME_ROOMS_STATE.update(
-                    deps.storage,
-                    params.game_room_key.clone(),
-                    |_| -> StdResult<_> { Ok(pre_game_room_state.get_finish_state(params.result)) },
+            GameRoomStatus::TeamWin { team } => {
+                settle_team_room(
+                    storage,
+                    game_room_id,
+                    &pre_game_room_state,
+                    team,
+                    &fee_config,
                 )?;
+            }
+            // the uneven stakes can't be halved out of the pool
+            result if pre_game_room_state.is_handicapped() => {
+                settle_handicap_room(
+                    storage,
+                    game_room_id,
+                    &pre_game_room_state,
+                    result,
+                    winner,
+                    &fee_config,
+                )?;
+            }
+            GameRoomStatus::Win { .. } | GameRoomStatus::Draw {} => {
+                let stake = cal_stake(pre_game_room_state.prize_pool);
+                let (payouts, fees) =
+                    even_duel_payouts(pre_game_room_state.prize_pool, &fee_config, winner)?;
 
-                // increasing the winner balance
-                BALANCES.update(
-                    deps.storage,
-                    &Addr::unchecked(pre_game_room_state.contestant1),
-                    |balance: Option<Balance>| -> StdResult<_> {
-                        Ok(balance.unwrap_or_default().unlock_and_decrease(
-                            cal_min_required(pre_game_room_state.prize_pool, Uint128::zero()),
-                            Uint128::zero(),
-                        ))
-                    },
+                // every stake is spent and the payouts are credited back
+                let contestants = pre_game_room_state.contestants();
+                for (contestant, payout) in contestants.iter().zip(&payouts) {
+                    BALANCES.update(
+                        storage,
+                        &Addr::unchecked(*contestant),
+                        |balance: Option<Balance>| -> StdResult<_> {
+                            Ok(balance
+                                .unwrap_or_default()
+                                .unlock_and_decrease(stake, stake)
+                                .total_increase(*payout))
+                        },
+                    )?;
+                }
+                credit_fees(
+                    storage,
+                    &contestants
+                        .iter()
+                        .map(|contestant| contestant.as_str())
+                        .zip(fees)
+                        .collect::<Vec<_>>(),
                 )?;
 
-                // decreasing the loser balance
-                BALANCES.update(
-                    deps.storage,
-                    &Addr::unchecked(pre_game_room_state.contestant2),
-                    |balance: Option<Balance>| -> StdResult<_> {
-                        Ok(balance.unwrap_or_default().unlock_and_decrease(
-                            cal_min_required(pre_game_room_state.prize_pool, Uint128::zero()),
-                            Uint128::zero(),
-                        ))
-                    },
+                game_rooms_state().save(
+                    storage,
+                    game_room_id,
+                    &pre_game_room_state.get_finish_state(result.clone()),
                 )?;
             }
         }
 
+        // keeping the record of the room for the players stats, history and ratings,
+        // the team rooms have no single opponent to rate the members against
+        record_match(
+            storage,
+            block,
+            game_room_id,
+            &pre_game_room_state,
+            &result,
+            winner,
+            &fee_config,
+        )?;
+        if !pre_game_room_state.is_team() {
+            update_ratings(storage, block, game_room_id, &pre_game_room_state, &result)?;
+        }
+
         // changing the game room status to finished to be able to be ongoing later
 
         Ok(Response::new().add_attribute("action", "finish_game_room"))
@@ -441,20 +849,17 @@ pub mod execute {
         info: MessageInfo,
         params: CollectFeesParams,
     ) -> Result<Response, ContractError> {
-        // loading the admin
-        let admin_addr = ADMIN.load(deps.storage)?;
-
-        // checking that the admin is sending the request
-        if info.sender != admin_addr {
-            return Err(crate::error::ContractError::Unauthorized {});
+        // checking that the treasurer is sending the request, the fees are held by the ledger
+        ensure_role(deps.as_ref(), &info, Role::Treasurer)?;
+        ensure_not_paused(deps.storage, Operation::Withdrawals)?;
+        let mut ledger = FEE_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+        if params.amount > ledger.available() {
+            return Err(error::ContractError::InsufficientFees {
+                available: ledger.available(),
+            });
         }
-        BALANCES.update(
-            deps.storage,
-            &admin_addr,
-            |balance: Option<Balance>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default().total_decrease(params.amount))
-            },
-        )?;
+        ledger.collected += params.amount;
+        FEE_LEDGER.save(deps.storage, &ledger)?;
         // creating the the transfer msg
         let msg = cosmwasm_std::WasmMsg::Execute {
             contract_addr: ENIGMA_DUEL_TOKEN.load(deps.storage)?.into(),
@@ -466,7 +871,7 @@ pub mod execute {
         };
 
         let withdraw_data = Withdraw {
-            user: Some(admin_addr.into_string()),
+            user: None,
             amount: params.amount,
             receiver: params.receiver,
         };
@@ -476,30 +881,196 @@ pub mod execute {
             .add_attribute("request_data", withdraw_data.to_string())
             .add_message(msg))
     }
+
+    pub fn propose_admin(
+        deps: DepsMut,
+        info: MessageInfo,
+        new_admin: String,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(deps.as_ref(), &info)?;
+
+        // proposing again overrides the previous proposal
+        let new_admin = deps.api.addr_validate(&new_admin)?;
+        PENDING_ADMIN.save(deps.storage, &new_admin)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "propose_admin")
+            .add_attribute("admin", info.sender)
+            .add_attribute("pending_admin", new_admin))
+    }
+
+    pub fn accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let pending_admin = PENDING_ADMIN
+            .may_load(deps.storage)?
+            .ok_or(error::ContractError::NoPendingAdmin {})?;
+
+        if info.sender != pending_admin {
+            return Err(error::ContractError::Unauthorized {});
+        }
+
+        // the collected fees are in the ledger, the balance of the old admin is its own
+        let old_admin = ADMIN.load(deps.storage)?;
+        ADMIN.save(deps.storage, &pending_admin)?;
+        PENDING_ADMIN.remove(deps.storage);
+        transfer_roles(deps.storage, &old_admin, Some(&pending_admin))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_admin")
+            .add_attribute("old_admin", old_admin)
+            .add_attribute("new_admin", pending_admin))
+    }
+
+    pub fn renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let admin = ensure_admin(deps.as_ref(), &info)?;
+
+        // the collected fees stay in the ledger for the treasurers
+        ADMIN.remove(deps.storage);
+        PENDING_ADMIN.remove(deps.storage);
+        transfer_roles(deps.storage, &admin, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "renounce_admin")
+            .add_attribute("old_admin", info.sender))
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
+        QueryMsg::GetAdmin {} => to_json_binary(&GetAdminResp {
+            admin: ADMIN.may_load(deps.storage)?.map(Addr::into_string),
+            pending_admin: PENDING_ADMIN.may_load(deps.storage)?.map(Addr::into_string),
+        }),
+        QueryMsg::ListRoleMembers {
+            role,
+            start_after,
+            limit,
+        } => to_json_binary(&query_role_members(deps, role, start_after, limit)?),
+        QueryMsg::GetUserRoles { user } => to_json_binary(&query_user_roles(deps, user)?),
+        QueryMsg::GetPauseState {} => to_json_binary(&query_pause_state(deps)?),
+        QueryMsg::GetConsentKey { user } => to_json_binary(&query_consent_key(deps, user)?),
+        QueryMsg::GetRoomTimeout {} => to_json_binary(&GetRoomTimeoutResp {
+            room_timeout: ROOM_TIMEOUT
+                .may_load(deps.storage)?
+                .unwrap_or(DEFAULT_ROOM_TIMEOUT),
+        }),
+        QueryMsg::GetPlayerStats { player } => to_json_binary(&query_player_stats(deps, player)?),
+        QueryMsg::ListPlayerHistory {
+            player,
+            start_after,
+            limit,
+        } => to_json_binary(&query_player_history(deps, player, start_after, limit)?),
+        QueryMsg::GetRating { player } => to_json_binary(&query_rating(deps, player)?),
+        QueryMsg::ListRatingHistory {
+            player,
+            start_after,
+            limit,
+        } => to_json_binary(&query_rating_history(deps, player, start_after, limit)?),
+        QueryMsg::GetEloKFactor {} => to_json_binary(&query_k_factor(deps)?),
+        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::ListFeeRecipients {} => to_json_binary(&query_fee_recipients(deps)?),
+        QueryMsg::GetReferrer { player } => to_json_binary(&query_referrer(deps, player)?),
+        QueryMsg::SimulateRoom {
+            amount,
+            contestants,
+        } => to_json_binary(&query_simulate_room(deps, amount, contestants)?),
+        QueryMsg::Leaderboard {
+            kind,
+            start_after,
+            limit,
+        } => to_json_binary(&query_leaderboard(deps, kind, start_after, limit)?),
+        QueryMsg::GetQueueEntry { player } => to_json_binary(&query_queue_entry(deps, player)?),
+        QueryMsg::ListQueue {
+            stake,
+            start_after,
+            limit,
+        } => to_json_binary(&query_queue(deps, stake, start_after, limit)?),
+        QueryMsg::GetTournament { tournament_id } => {
+            to_json_binary(&query_tournament(deps, tournament_id)?)
+        }
+        QueryMsg::GetTournamentBracket { tournament_id } => {
+            to_json_binary(&query_tournament_bracket(deps, tournament_id)?)
+        }
+        QueryMsg::ListTournaments { start_after, limit } => {
+            to_json_binary(&query_tournaments(deps, start_after, limit)?)
+        }
+        QueryMsg::GetSeriesScore { game_room_id } => {
+            to_json_binary(&query_series_score(deps, game_room_id)?)
+        }
+        QueryMsg::GetLeague { league_id } => to_json_binary(&query_league(deps, league_id)?),
+        QueryMsg::ListLeagues { start_after, limit } => {
+            to_json_binary(&query_leagues(deps, start_after, limit)?)
+        }
+        QueryMsg::LeagueStandings {
+            league_id,
+            season,
+            division,
+            start_after,
+            limit,
+        } => to_json_binary(&query_league_standings(
+            deps,
+            league_id,
+            season,
+            division,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ListLeagueFixtures {
+            league_id,
+            season,
+            start_after,
+            limit,
+        } => to_json_binary(&query_league_fixtures(
+            deps,
+            league_id,
+            season,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetChallenge { challenge_id } => {
+            to_json_binary(&query_challenge(deps, challenge_id)?)
+        }
+        QueryMsg::ListChallenges { start_after, limit } => {
+            to_json_binary(&query_challenges(deps, start_after, limit)?)
+        }
         QueryMsg::GetCollectedFees {} => {
-            let admin_addr = ADMIN.load(deps.storage)?;
-            let col_fees = BALANCES
-                .load(deps.storage, &admin_addr)
-                .unwrap_or(Balance::new_zero());
-            to_json_binary(&col_fees.total)
-        }
-        QueryMsg::GetGameRoomState { game_room_key } => Ok(to_json_binary(
-            &GAME_ROOMS_STATE
-                .may_load(deps.storage, game_room_key)
-                .unwrap()
-                .unwrap(),
-        )
-        .unwrap()),
-        QueryMsg::GetTotalGames {} => Ok(to_json_binary(
-            &GAME_ROOMS_COUNT
-                .load(deps.storage)
-                .unwrap_or(Uint256::zero()),
-        )
+            let ledger = FEE_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+            to_json_binary(&ledger.available())
+        }
+        QueryMsg::GetFeeLedger {} => {
+            to_json_binary(&FEE_LEDGER.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::GetGameRoomState { game_room_id } => {
+            to_json_binary(&game_rooms_state().load(deps.storage, game_room_id)?)
+        }
+        QueryMsg::ListRoomsByPair {
+            contestant1,
+            contestant2,
+            start_after,
+            limit,
+        } => to_json_binary(&query_rooms_by_pair(
+            deps,
+            contestant1,
+            contestant2,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ListGameRooms {
+            start_after,
+            limit,
+            status,
+        } => to_json_binary(&query_game_rooms(deps, start_after, limit, status)?),
+        QueryMsg::ListRoomsByPlayer {
+            player,
+            start_after,
+            limit,
+        } => to_json_binary(&query_rooms_by_player(deps, player, start_after, limit)?),
+        QueryMsg::ListActiveRooms { start_after, limit } => {
+            to_json_binary(&query_active_rooms(deps, start_after, limit)?)
+        }
+        QueryMsg::GetTotalGames {} => Ok(to_json_binary(&Uint256::from(
+            GAME_ROOMS_COUNT.load(deps.storage).unwrap_or_default(),
+        ))
         .unwrap()),
         QueryMsg::GetUserBalance { user } => {
             let balance: Uint128 = BALANCES
@@ -521,3 +1092,328 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         }
     }
 }
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // rejects foreign contracts and downgrades, bumps the stored version otherwise
+    let original_version =
+        ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    migrate::run_migrations(deps, &env, &msg, &original_version)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", original_version.to_string())
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+pub mod migrate {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{Order, StdError};
+    use cw_storage_plus::{Item, Map};
+    use semver::Version;
+
+    use crate::error::InsufficientBalanceErr;
+    use crate::state::{player_stats, ratings};
+
+    use super::*;
+
+    /// a single storage upgrade step.
+    pub type MigrationStep = fn(DepsMut, &Env, &MigrateMsg) -> Result<(), ContractError>;
+
+    /// storage upgrade steps, each one gated by the contract version that introduced the new layout.
+    /// a step runs only when the deployed contract is older than its version.
+    /// keep this list in ascending version order so the steps run in the same order they were released.
+    pub const MIGRATIONS: &[(&str, MigrationStep)] = &[
+        ("0.3.0", grant_admin_roles),
+        ("0.4.0", grant_admin_pauser_role),
+        ("0.6.0", add_room_deadlines),
+        ("0.7.0", number_game_rooms),
+        ("0.8.0", index_game_rooms),
+        ("0.11.0", index_leaderboards),
+        ("0.22.0", migrate_fee_config),
+        ("0.24.0", move_admin_fees),
+        ("0.26.0", index_every_contestant),
+        ("0.32.0", key_fee_accrued_by_destination),
+    ];
+
+    pub fn run_migrations(
+        mut deps: DepsMut,
+        env: &Env,
+        msg: &MigrateMsg,
+        original_version: &Version,
+    ) -> Result<(), ContractError> {
+        for (version, step) in MIGRATIONS {
+            let step_version = version
+                .parse::<Version>()
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+            if *original_version < step_version {
+                step(deps.branch(), env, msg)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 0.3.0 moved the access control to the role registry, the existing admin keeps every role.
+    pub fn grant_admin_roles(
+        deps: DepsMut,
+        _env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        if let Some(admin) = ADMIN.may_load(deps.storage)? {
+            for role in Role::all() {
+                ROLES.save(deps.storage, (role.key(), &admin), &Empty {})?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 0.4.0 added the pauser role, the existing admin can halt the contract right after the upgrade.
+    pub fn grant_admin_pauser_role(
+        deps: DepsMut,
+        _env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        if let Some(admin) = ADMIN.may_load(deps.storage)? {
+            ROLES.save(deps.storage, (Role::Pauser.key(), &admin), &Empty {})?;
+        }
+        Ok(())
+    }
+
+    // the game room layout before 0.6.0
+    #[cw_serde]
+    pub struct GameRoomsStateV1 {
+        pub contestant1: String,
+        pub contestant2: String,
+        pub prize_pool: Uint128,
+        pub status: GameRoomStatus,
+    }
+
+    // the game room layout before 0.7.0
+    #[cw_serde]
+    pub struct GameRoomsStateV2 {
+        pub contestant1: String,
+        pub contestant2: String,
+        pub prize_pool: Uint128,
+        pub status: GameRoomStatus,
+        pub created_at: Timestamp,
+        pub created_height: u64,
+        pub deadline: Timestamp,
+    }
+
+    // before 0.7.0 the rooms were keyed by the base64 of the concatenated contestants
+    const GAME_ROOMS_STATE_V1: Map<String, GameRoomsStateV1> = Map::new("game_rooms");
+    const GAME_ROOMS_STATE_V2: Map<String, GameRoomsStateV2> = Map::new("game_rooms");
+    // before 0.7.0 the count held the number of started rooms
+    const GAME_ROOMS_COUNT_V1: Item<Uint256> = Item::new("game_room_count");
+
+    /// 0.6.0 added the creation time and the deadline to the rooms,
+    /// the existing rooms count as created by the migration.
+    pub fn add_room_deadlines(
+        deps: DepsMut,
+        env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        let deadline = room_deadline(deps.storage, &env.block)?;
+        let rooms = GAME_ROOMS_STATE_V1
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        for (key, room) in rooms {
+            GAME_ROOMS_STATE_V2.save(
+                deps.storage,
+                key,
+                &GameRoomsStateV2 {
+                    contestant1: room.contestant1,
+                    contestant2: room.contestant2,
+                    prize_pool: room.prize_pool,
+                    status: room.status,
+                    created_at: env.block.time,
+                    created_height: env.block.height,
+                    deadline,
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 0.7.0 keyed the rooms by a numeric id, the existing rooms get the ids following the old count.
+    /// `GetTotalGames` returns the last id from then on, the existing rooms count on top of the old count.
+    pub fn number_game_rooms(
+        deps: DepsMut,
+        _env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        let count = GAME_ROOMS_COUNT_V1
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .to_string()
+            .parse::<u64>()
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        let rooms = GAME_ROOMS_STATE_V2
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut game_room_id = count;
+        for (key, room) in rooms {
+            game_room_id += 1;
+            game_rooms_state().save(
+                deps.storage,
+                game_room_id,
+                &GameRoomsState {
+                    contestant1: room.contestant1,
+                    contestant2: room.contestant2,
+                    prize_pool: room.prize_pool,
+                    status: room.status,
+                    created_at: room.created_at,
+                    created_height: room.created_height,
+                    deadline: room.deadline,
+                    secret_hash: None,
+                    extra_contestants: vec![],
+                    payout_table: vec![],
+                    series: None,
+                    stakes: vec![],
+                    payout_policy: Default::default(),
+                    teams: vec![],
+                    fee_config: None,
+                },
+            )?;
+            GAME_ROOMS_STATE_V2.remove(deps.storage, key);
+        }
+        GAME_ROOMS_COUNT.save(deps.storage, &game_room_id)?;
+        Ok(())
+    }
+
+    /// 0.8.0 indexed the rooms by contestant and status, the existing rooms are saved again to fill the new indexes.
+    pub fn index_game_rooms(
+        deps: DepsMut,
+        _env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        let rooms = game_rooms_state()
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        for (game_room_id, room) in rooms {
+            game_rooms_state().save(deps.storage, game_room_id, &room)?;
+        }
+        Ok(())
+    }
+
+    /// 0.11.0 indexed the player stats and ratings for the leaderboards,
+    /// the existing entries are saved again to fill the new indexes.
+    pub fn index_leaderboards(
+        deps: DepsMut,
+        _env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        let stats = player_stats()
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (player, entry) in stats {
+            player_stats().save(deps.storage, &player, &entry)?;
+        }
+
+        let player_ratings = ratings()
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (player, rating) in player_ratings {
+            ratings().save(deps.storage, &player, &rating)?;
+        }
+        Ok(())
+    }
+
+    /// 0.22.0 replaced the single flat fee with a fee schedule, the old fee becomes a flat one.
+    pub fn migrate_fee_config(
+        deps: DepsMut,
+        _env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        const FEE: Item<Uint128> = Item::new("fee");
+
+        if let Some(fee) = FEE.may_load(deps.storage)? {
+            FEE_CONFIG.save(deps.storage, &FeeConfig::flat(fee))?;
+            FEE.remove(deps.storage);
+        }
+        Ok(())
+    }
+
+    /// 0.24.0 kept the fees in their own ledger. the fees credited to the admin balance so far can't be
+    /// told apart from the deposits and winnings of the admin, so only the `admin_fees` of the migrate
+    /// message move into it. the locked part stays since it belongs to the ongoing games of the admin.
+    pub fn move_admin_fees(
+        deps: DepsMut,
+        _env: &Env,
+        msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        let fees = msg.admin_fees.unwrap_or_default();
+        if fees.is_zero() {
+            return Ok(());
+        }
+        let admin_addr = ADMIN.load(deps.storage)?;
+        let balance = BALANCES
+            .may_load(deps.storage, &admin_addr)?
+            .unwrap_or_default();
+
+        let available = balance.clone().available_balance();
+        if fees > available {
+            return Err(ContractError::InsufficientBalance(InsufficientBalanceErr {
+                min_required: fees,
+                current_balance: available,
+                user: admin_addr.to_string(),
+            }));
+        }
+        BALANCES.save(deps.storage, &admin_addr, &balance.total_decrease(fees))?;
+        let mut ledger = FEE_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+        ledger.accrued += fees;
+        FEE_LEDGER.save(deps.storage, &ledger)?;
+        Ok(())
+    }
+
+    /// 0.26.0 indexed the rooms by every contestant instead of the first two,
+    /// the existing rooms are added to the new index.
+    pub fn index_every_contestant(
+        deps: DepsMut,
+        _env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        let rooms = game_rooms_state()
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        for (game_room_id, room) in rooms {
+            index_player_rooms(deps.storage, game_room_id, &room)?;
+        }
+        Ok(())
+    }
+
+    // before 0.32.0 the accrued fees were kept by recipient name
+    const FEE_ACCRUED_V1: Map<&str, Uint128> = Map::new("fee_accrued");
+
+    /// 0.32.0 kept the accrued fees by destination so a renamed recipient keeps them.
+    /// the current recipients carry theirs over, the ones of the recipients removed before are dropped.
+    pub fn key_fee_accrued_by_destination(
+        deps: DepsMut,
+        _env: &Env,
+        _msg: &MigrateMsg,
+    ) -> Result<(), ContractError> {
+        for recipient in FEE_RECIPIENTS.may_load(deps.storage)?.unwrap_or_default() {
+            let accrued = FEE_ACCRUED_V1
+                .may_load(deps.storage, &recipient.name)?
+                .unwrap_or_default();
+            FEE_ACCRUED.update(
+                deps.storage,
+                recipient.destination.key(),
+                |total| -> StdResult<_> { Ok(total.unwrap_or_default() + accrued) },
+            )?;
+        }
+        let names = FEE_ACCRUED_V1
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for name in names {
+            FEE_ACCRUED_V1.remove(deps.storage, &name);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use cosmwasm_std::{Deps, Order, StdResult};
+use cw_storage_plus::Bound;
+
+use crate::helpers::pair_key;
+use crate::msg::{GameRoomResp, GameRoomStatusKind, ListGameRoomsResp};
+use crate::state::{game_rooms_state, GameRoomsState, PLAYER_ROOMS};
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn query_rooms_by_pair(
+    deps: Deps,
+    contestant1: String,
+    contestant2: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListGameRoomsResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let rooms = game_rooms_state()
+        .idx
+        .pair
+        .prefix(pair_key(&contestant1, &contestant2))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(game_room_id, state)| GameRoomResp {
+                game_room_id,
+                state,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListGameRoomsResp { rooms })
+}
+
+pub fn query_game_rooms(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    status: Option<GameRoomStatusKind>,
+) -> StdResult<ListGameRoomsResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let rooms = match status {
+        Some(status) => rooms_in_status(deps, status, start_after, limit)?,
+        None => game_rooms_state()
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    Ok(to_list_resp(rooms))
+}
+
+pub fn query_rooms_by_player(
+    deps: Deps,
+    player: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListGameRoomsResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let rooms = PLAYER_ROOMS
+        .prefix(&player)
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|game_room_id| {
+            let game_room_id = game_room_id?;
+            Ok((
+                game_room_id,
+                game_rooms_state().load(deps.storage, game_room_id)?,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(to_list_resp(rooms))
+}
+
+pub fn query_active_rooms(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListGameRoomsResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let pending = rooms_in_status(deps, GameRoomStatusKind::Pending, start_after, limit)?;
+    let started = rooms_in_status(deps, GameRoomStatusKind::Started, start_after, limit)?;
+
+    Ok(to_list_resp(merge_rooms(pending, started, limit)))
+}
+
+fn rooms_in_status(
+    deps: Deps,
+    status: GameRoomStatusKind,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<Vec<(u64, GameRoomsState)>> {
+    game_rooms_state()
+        .idx
+        .status
+        .prefix(status.key().to_string())
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
+
+// the first `limit` rooms of two id ordered lists, a room found in both is kept once.
+fn merge_rooms(
+    mut rooms: Vec<(u64, GameRoomsState)>,
+    other: Vec<(u64, GameRoomsState)>,
+    limit: usize,
+) -> Vec<(u64, GameRoomsState)> {
+    rooms.extend(other);
+    rooms.sort_by_key(|(game_room_id, _)| *game_room_id);
+    rooms.dedup_by_key(|(game_room_id, _)| *game_room_id);
+    rooms.truncate(limit);
+    rooms
+}
+
+fn to_list_resp(rooms: Vec<(u64, GameRoomsState)>) -> ListGameRoomsResp {
+    ListGameRoomsResp {
+        rooms: rooms
+            .into_iter()
+            .map(|(game_room_id, state)| GameRoomResp {
+                game_room_id,
+                state,
+            })
+            .collect(),
+    }
+}
@@ -2,6 +2,8 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
+use crate::msg::Role;
+
 #[derive(Error, Debug)]
 pub enum ContractError {
     #[error("{0}")]
@@ -10,15 +12,164 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Unauthorized, missing the {role} role")]
+    MissingRole { role: Role },
+
+    #[error("{operation} paused for maintenance")]
+    OperationPaused { operation: String },
+
     #[error("insufficient Balance")]
     InsufficientBalance(InsufficientBalanceErr),
 
+    #[error("Only {available} of the collected fees are available")]
+    InsufficientFees { available: Uint128 },
+
     #[error("Game room is not started yet")]
     GameRoomNotStarted {},
 
     #[error("Game room already started")]
     GameRoomAlreadyStarted {},
 
+    #[error("No pending admin to accept")]
+    NoPendingAdmin {},
+
+    #[error("Game room is not waiting for contestants")]
+    GameRoomNotPending {},
+
+    #[error("Not a contestant of the game room")]
+    NotAContestant {},
+
+    #[error("No consent key registered for {user}")]
+    ConsentKeyNotRegistered { user: String },
+
+    #[error("Invalid consent signature from {user}")]
+    InvalidConsentSignature { user: String },
+
+    #[error("Consent expired")]
+    ConsentExpired {},
+
+    #[error("Consent already used")]
+    ConsentAlreadyUsed {},
+
+    #[error("Game room passed its deadline")]
+    GameRoomExpired {},
+
+    #[error("Game room deadline not reached yet")]
+    GameRoomDeadlineNotReached {},
+
+    #[error("Room timeout must be at least {min} seconds")]
+    InvalidRoomTimeout { min: u64 },
+
+    #[error("Elo K-factor must be greater than zero")]
+    InvalidKFactor {},
+
+    #[error("Already waiting in the matchmaking queue")]
+    AlreadyQueued {},
+
+    #[error("Not waiting in the matchmaking queue")]
+    NotQueued {},
+
+    #[error("Stake must be greater than zero and cover the fee")]
+    InvalidStake {},
+
+    #[error("Challenge expired")]
+    ChallengeExpired {},
+
+    #[error("The challenge is addressed to another opponent")]
+    NotChallengeOpponent {},
+
+    #[error("Cannot play against yourself")]
+    OwnChallenge {},
+
+    #[error("Invalid invite secret")]
+    InvalidRoomSecret {},
+
+    #[error("A multi-player room needs {min} to {max} distinct contestants")]
+    InvalidContestants { min: u32, max: u32 },
+
+    #[error("The payout table must split the whole pool and pay at most one share per contestant")]
+    InvalidPayoutTable {},
+
+    #[error("The ranking must list every contestant of the room exactly once")]
+    InvalidRanking {},
+
+    #[error("Multi-player rooms settle with a ranking, duels with a win or a draw")]
+    InvalidRoomResult {},
+
+    #[error("A tournament takes {min} to {max} players")]
+    InvalidTournamentSize { min: u32, max: u32 },
+
+    #[error("Tournament registration is closed")]
+    TournamentNotOpen {},
+
+    #[error("Tournament is full")]
+    TournamentFull {},
+
+    #[error("Already registered")]
+    AlreadyRegistered {},
+
+    #[error("Not enough players registered")]
+    NotEnoughPlayers {},
+
+    #[error("Tournament is not running")]
+    TournamentNotRunning {},
+
+    #[error("Tournament matches need a winner")]
+    TournamentMatchNeedsWinner {},
+
+    #[error("Tournament matches can't expire, the bracket waits for their winner")]
+    TournamentMatchCantExpire {},
+
+    #[error("A league takes 1 to {max_divisions} divisions of 2 to {max_players} players and promotes at most half of a division")]
+    InvalidLeagueSize {
+        max_divisions: u32,
+        max_players: u32,
+    },
+
+    #[error("League registration is closed")]
+    LeagueNotOpen {},
+
+    #[error("Every division of the league is full")]
+    LeagueFull {},
+
+    #[error("League season is not running")]
+    LeagueNotRunning {},
+
+    #[error("League fixtures can't expire, the standings wait for their result")]
+    LeagueFixtureCantExpire {},
+
+    #[error("A series is played over an odd number of games from 3 to {max}")]
+    InvalidBestOf { max: u32 },
+
+    #[error("Game room is not a series")]
+    NotASeries {},
+
+    #[error("Series rooms settle through the reported games")]
+    SeriesInProgress {},
+
+    #[error("The scores must list both contestants exactly once")]
+    InvalidScores {},
+
+    #[error("A margin refund can't give back more than the whole stake")]
+    InvalidPayoutPolicy {},
+
+    #[error("Each team needs 1 to {max} members and nobody can play twice")]
+    InvalidTeams { max: u32 },
+
+    #[error("Fee basis points can't pass 10000, the minimum can't pass the maximum and the tiers must ascend")]
+    InvalidFeeConfig {},
+
+    #[error("Fee recipients need unique names and positive weights, at most {max} of them and one referrer")]
+    InvalidFeeRecipients { max: u32 },
+
+    #[error(
+        "The referrer must hold the referrer role, can't be the player and can only be set once"
+    )]
+    InvalidReferrer {},
+
+    #[error("Only the even duels of two contestants can be simulated")]
+    UnsupportedRoomKind {},
+
     #[error("Game room load error")]
     GameRoomLoadError { msg: String },
 }
@@ -0,0 +1,343 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult, Storage,
+    Uint128, WasmMsg,
+};
+
+use crate::error::ContractError;
+use crate::helpers::{cal_stake, even_duel_payouts};
+use crate::msg::{
+    ConfigResp, FeeRecipientResp, GameRoomStatus, GetReferrerResp, ListFeeRecipientsResp, Role,
+    SimulateRoomResp, SimulatedContestant, SimulatedOutcome, SimulationAmount,
+};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::roles::{ensure_admin, ensure_role, has_role};
+use crate::state::{
+    Balance, FeeConfig, FeeDestination, FeeRecipient, GameRoomsState, BALANCES, ENIGMA_DUEL_TOKEN,
+    FEE_ACCRUED, FEE_CONFIG, FEE_LEDGER, FEE_RECIPIENTS, PENDING_BURN, REFERRERS,
+};
+
+const MAX_BPS: u16 = 10_000;
+pub const MAX_FEE_RECIPIENTS: u32 = 10;
+
+pub fn ensure_valid_fee_config(fee_config: &FeeConfig) -> Result<(), ContractError> {
+    let caps_ordered = match (fee_config.min, fee_config.max) {
+        (Some(min), Some(max)) => min <= max,
+        _ => true,
+    };
+    if fee_config.bps > MAX_BPS
+        || !caps_ordered
+        || fee_config.tiers.iter().any(|tier| tier.bps > MAX_BPS)
+        || fee_config
+            .tiers
+            .windows(2)
+            .any(|tiers| tiers[0].min_stake >= tiers[1].min_stake)
+    {
+        return Err(ContractError::InvalidFeeConfig {});
+    }
+    Ok(())
+}
+
+// the fee schedule the room was created with, the contract wide one for new rooms
+// and for the rooms older than the schedules kept on the room
+pub fn load_fee_config(
+    storage: &dyn Storage,
+    game_room: Option<&GameRoomsState>,
+) -> StdResult<FeeConfig> {
+    match game_room.and_then(|game_room| game_room.fee_config.clone()) {
+        Some(fee_config) => Ok(fee_config),
+        None => FEE_CONFIG.load(storage),
+    }
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_config: FeeConfig,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    ensure_valid_fee_config(&fee_config)?;
+
+    // applies to the rooms created from now on, the existing rooms keep their schedule
+    FEE_CONFIG.save(deps.storage, &fee_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_config"),
+        attr("flat_fee", fee_config.flat),
+        attr("fee_bps", fee_config.bps.to_string()),
+    ]))
+}
+
+// splits what every contestant paid between the fee recipients by their weights.
+// the rounding dust goes to the first recipient, the referrer shares of the contestants
+// nobody referred go to the fee ledger like every fee while there are no recipients.
+pub fn credit_fees(storage: &mut dyn Storage, fees: &[(&str, Uint128)]) -> StdResult<()> {
+    let total: Uint128 = fees.iter().map(|(_, fee)| *fee).sum();
+    if total.is_zero() {
+        return Ok(());
+    }
+    let recipients = FEE_RECIPIENTS.may_load(storage)?.unwrap_or_default();
+    if recipients.is_empty() {
+        return credit_ledger(storage, total);
+    }
+
+    let total_weight: u128 = recipients
+        .iter()
+        .map(|recipient| recipient.weight as u128)
+        .sum();
+    let mut shares: Vec<Uint128> = recipients
+        .iter()
+        .map(|recipient| total.multiply_ratio(recipient.weight as u128, total_weight))
+        .collect();
+    let split = shares.iter().sum::<Uint128>();
+    shares[0] += total - split;
+
+    for (recipient, share) in recipients.iter().zip(shares) {
+        let credited = match &recipient.destination {
+            FeeDestination::Balance { addr } => {
+                credit_balance(storage, &Addr::unchecked(addr), share)?;
+                share
+            }
+            FeeDestination::Burn {} => {
+                let pending = PENDING_BURN.may_load(storage)?.unwrap_or_default();
+                PENDING_BURN.save(storage, &(pending + share))?;
+                share
+            }
+            FeeDestination::Referrer {} => credit_referrers(storage, fees, total, share)?,
+        };
+        FEE_ACCRUED.update(
+            storage,
+            recipient.destination.key(),
+            |accrued| -> StdResult<_> { Ok(accrued.unwrap_or_default() + credited) },
+        )?;
+    }
+    Ok(())
+}
+
+// the referrer share is split in proportion to what each contestant paid,
+// the shares of the referrers whose role was revoked go to the fee ledger.
+fn credit_referrers(
+    storage: &mut dyn Storage,
+    fees: &[(&str, Uint128)],
+    total: Uint128,
+    share: Uint128,
+) -> StdResult<Uint128> {
+    let mut credited = Uint128::zero();
+    for (player, fee) in fees {
+        let Some(referrer) = REFERRERS.may_load(storage, &Addr::unchecked(*player))? else {
+            continue;
+        };
+        if !has_role(storage, &Role::Referrer, &referrer) {
+            continue;
+        }
+        let part = share.multiply_ratio(*fee, total);
+        credit_balance(storage, &referrer, part)?;
+        credited += part;
+    }
+    credit_ledger(storage, share - credited)?;
+    Ok(credited)
+}
+
+fn credit_ledger(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let mut ledger = FEE_LEDGER.may_load(storage)?.unwrap_or_default();
+    ledger.accrued += amount;
+    FEE_LEDGER.save(storage, &ledger)
+}
+
+fn credit_balance(storage: &mut dyn Storage, addr: &Addr, amount: Uint128) -> StdResult<()> {
+    BALANCES.update(storage, addr, |balance: Option<Balance>| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().total_increase(amount))
+    })?;
+    Ok(())
+}
+
+pub fn execute_update_fee_recipients(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipients: Vec<FeeRecipient>,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+
+    let invalid = ContractError::InvalidFeeRecipients {
+        max: MAX_FEE_RECIPIENTS,
+    };
+    let mut names: Vec<&str> = recipients
+        .iter()
+        .map(|recipient| recipient.name.as_str())
+        .collect();
+    names.sort();
+    names.dedup();
+    // a destination is listed once so what it accrued isn't shared between recipients
+    let mut destinations: Vec<&str> = recipients
+        .iter()
+        .map(|recipient| recipient.destination.key())
+        .collect();
+    destinations.sort();
+    destinations.dedup();
+    if recipients.len() > MAX_FEE_RECIPIENTS as usize
+        || names.len() != recipients.len()
+        || destinations.len() != recipients.len()
+        || recipients
+            .iter()
+            .any(|recipient| recipient.name.is_empty() || recipient.weight == 0)
+    {
+        return Err(invalid);
+    }
+    for recipient in &recipients {
+        if let FeeDestination::Balance { addr } = &recipient.destination {
+            deps.api.addr_validate(addr)?;
+        }
+    }
+
+    // what a destination accrued carries over when it's listed again
+    FEE_RECIPIENTS.save(deps.storage, &recipients)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_fee_recipients"),
+        attr("recipients", recipients.len().to_string()),
+    ]))
+}
+
+pub fn execute_burn_fees(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info, Role::Treasurer)?;
+    ensure_not_paused(deps.storage, Operation::Withdrawals)?;
+
+    let amount = PENDING_BURN.may_load(deps.storage)?.unwrap_or_default();
+    PENDING_BURN.save(deps.storage, &Uint128::zero())?;
+
+    let mut res =
+        Response::new().add_attributes(vec![attr("action", "burn_fees"), attr("amount", amount)]);
+    if !amount.is_zero() {
+        res = res.add_message(WasmMsg::Execute {
+            contract_addr: ENIGMA_DUEL_TOKEN.load(deps.storage)?.into(),
+            msg: to_json_binary(&cw20::Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        });
+    }
+    Ok(res)
+}
+
+pub fn execute_set_referrer(
+    deps: DepsMut,
+    info: MessageInfo,
+    referrer: String,
+) -> Result<Response, ContractError> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+    // the admin vets the referrers so nobody can refer themselves from a second account
+    if referrer == info.sender
+        || !has_role(deps.storage, &Role::Referrer, &referrer)
+        || REFERRERS.has(deps.storage, &info.sender)
+    {
+        return Err(ContractError::InvalidReferrer {});
+    }
+    REFERRERS.save(deps.storage, &info.sender, &referrer)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_referrer"),
+        attr("player", info.sender),
+        attr("referrer", referrer),
+    ]))
+}
+
+pub fn query_fee_recipients(deps: Deps) -> StdResult<ListFeeRecipientsResp> {
+    let recipients = FEE_RECIPIENTS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|recipient| {
+            let accrued = FEE_ACCRUED
+                .may_load(deps.storage, recipient.destination.key())?
+                .unwrap_or_default();
+            Ok(FeeRecipientResp { recipient, accrued })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ListFeeRecipientsResp {
+        recipients,
+        pending_burn: PENDING_BURN.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_referrer(deps: Deps, player: String) -> StdResult<GetReferrerResp> {
+    let referrer = REFERRERS.may_load(deps.storage, &Addr::unchecked(player))?;
+    Ok(GetReferrerResp {
+        referrer: referrer.map(Addr::into_string),
+    })
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResp> {
+    Ok(ConfigResp {
+        fee_config: FEE_CONFIG.load(deps.storage)?,
+    })
+}
+
+// previews an even duel with the payouts its settlement would credit, so the clients don't have to repeat it.
+// the rooms of more than two contestants settle by their own tables and aren't simulated.
+pub fn query_simulate_room(
+    deps: Deps,
+    amount: SimulationAmount,
+    contestants: Vec<String>,
+) -> StdResult<SimulateRoomResp> {
+    if contestants.len() != 2 {
+        return Err(StdError::generic_err(
+            ContractError::UnsupportedRoomKind {}.to_string(),
+        ));
+    }
+    if contestants[0] == contestants[1] {
+        return Err(StdError::generic_err(
+            "a duel takes two different contestants",
+        ));
+    }
+    let prize_pool = match amount {
+        SimulationAmount::PrizePool { amount } => amount,
+        SimulationAmount::Stake { amount } => amount.checked_mul(Uint128::new(2))?,
+    };
+    let stake = cal_stake(prize_pool);
+    let fee_config = load_fee_config(deps.storage, None)?;
+    let fee = fee_config.fee(stake);
+    if stake.is_zero() || fee * Uint128::new(2) > stake {
+        return Err(StdError::generic_err("the stake doesn't cover the fees"));
+    }
+    let draw_fee = fee_config.draw_fee(stake);
+
+    let simulated_contestants = contestants
+        .iter()
+        .map(|contestant| {
+            let available = BALANCES
+                .may_load(deps.storage, &Addr::unchecked(contestant))?
+                .unwrap_or_default()
+                .available_balance();
+            Ok(SimulatedContestant {
+                addr: contestant.clone(),
+                required_lock: stake,
+                available,
+                sufficient: available > stake,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // a win for each of the contestants and then the draw
+    let outcomes = [Some(0), Some(1), None]
+        .into_iter()
+        .map(|winner| {
+            let (payouts, fees) = even_duel_payouts(prize_pool, &fee_config, winner)?;
+            Ok(SimulatedOutcome {
+                result: match winner {
+                    Some(winner) => GameRoomStatus::Win {
+                        addr: contestants[winner].clone(),
+                    },
+                    None => GameRoomStatus::Draw {},
+                },
+                payouts,
+                fees: fees.into_iter().sum(),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SimulateRoomResp {
+        prize_pool,
+        stake,
+        fee,
+        draw_fee,
+        contestants: simulated_contestants,
+        outcomes,
+    })
+}
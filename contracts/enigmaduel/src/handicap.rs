@@ -0,0 +1,140 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage,
+    Uint128,
+};
+
+use crate::contract::execute::{ensure_valid_stake, room_deadline, save_new_game_room};
+use crate::error::ContractError;
+use crate::fees::{credit_fees, load_fee_config};
+use crate::msg::{CreateGameRoomResp, GameRoomStatus, Role};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::roles::ensure_role;
+use crate::state::{game_rooms_state, Balance, FeeConfig, GameRoomsState, BALANCES};
+
+// a duel where each contestant locks their own stake, e.g. the stronger player puts in
+// twice as much as the weaker one to even out the odds.
+pub fn execute_create_handicap_room(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contestant1: String,
+    contestant2: String,
+    stake1: Uint128,
+    stake2: Uint128,
+) -> Result<Response, ContractError> {
+    // sender must be a game operator
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    ensure_valid_stake(deps.storage, stake1)?;
+    ensure_valid_stake(deps.storage, stake2)?;
+    // the winner must never take back less than their own stake
+    let fee_config = load_fee_config(deps.storage, None)?;
+    if stake1.min(stake2) <= fee_config.fee(stake1) + fee_config.fee(stake2) {
+        return Err(ContractError::InvalidStake {});
+    }
+
+    // like the even duels nothing is locked until both of the contestants joined
+    let game_room = GameRoomsState {
+        contestant1,
+        contestant2,
+        prize_pool: stake1 + stake2,
+        status: GameRoomStatus::Pending { joined: vec![] },
+        created_at: env.block.time,
+        created_height: env.block.height,
+        deadline: room_deadline(deps.storage, &env.block)?,
+        secret_hash: None,
+        extra_contestants: vec![],
+        payout_table: vec![],
+        series: None,
+        stakes: vec![stake1, stake2],
+        payout_policy: Default::default(),
+        teams: vec![],
+        fee_config: Some(fee_config),
+    };
+    let game_room_id = save_new_game_room(deps.storage, &game_room)?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&CreateGameRoomResp(game_room_id))?)
+        .add_attributes(vec![
+            attr("action", "create_handicap_room"),
+            attr("room_id", game_room_id.to_string()),
+            attr("stake1", stake1),
+            attr("stake2", stake2),
+        ]))
+}
+
+// `winner` is the index of the winner the settlement checked against the contestants.
+// the winner takes the whole pool minus the fee of both contestants,
+// a draw gives every contestant their own stake back minus the draw fee on it.
+pub fn settle_handicap_room(
+    storage: &mut dyn Storage,
+    game_room_id: u64,
+    game_room: &GameRoomsState,
+    result: GameRoomStatus,
+    winner: Option<usize>,
+    fee_config: &FeeConfig,
+) -> Result<(), ContractError> {
+    let stakes = game_room.stakes();
+    match (&result, winner) {
+        (GameRoomStatus::Win { addr }, Some(winner)) => {
+            let (winner_stake, loser, loser_stake) = if winner == 0 {
+                (stakes[0], &game_room.contestant2, stakes[1])
+            } else {
+                (stakes[1], &game_room.contestant1, stakes[0])
+            };
+            let total_fee = fee_config.fee(stakes[0]) + fee_config.fee(stakes[1]);
+            let winnings = loser_stake.checked_sub(total_fee).map_err(StdError::from)?;
+
+            BALANCES.update(
+                storage,
+                &Addr::unchecked(addr),
+                |balance: Option<Balance>| -> StdResult<_> {
+                    Ok(balance
+                        .unwrap_or_default()
+                        .unlock_and_decrease(winner_stake, Uint128::zero())
+                        .total_increase(winnings))
+                },
+            )?;
+            BALANCES.update(
+                storage,
+                &Addr::unchecked(loser),
+                |balance: Option<Balance>| -> StdResult<_> {
+                    Ok(balance
+                        .unwrap_or_default()
+                        .unlock_and_decrease(loser_stake, loser_stake))
+                },
+            )?;
+            credit_fees(
+                storage,
+                &game_room
+                    .contestants()
+                    .into_iter()
+                    .zip(&stakes)
+                    .map(|(contestant, stake)| (contestant.as_str(), fee_config.fee(*stake)))
+                    .collect::<Vec<_>>(),
+            )?;
+        }
+        (GameRoomStatus::Draw {}, _) => {
+            let mut draw_fees = vec![];
+            for (contestant, stake) in game_room.contestants().into_iter().zip(stakes) {
+                let draw_fee = fee_config.draw_fee(stake);
+                BALANCES.update(
+                    storage,
+                    &Addr::unchecked(contestant),
+                    |balance: Option<Balance>| -> StdResult<_> {
+                        Ok(balance
+                            .unwrap_or_default()
+                            .unlock_and_decrease(stake, draw_fee))
+                    },
+                )?;
+                draw_fees.push((contestant.as_str(), draw_fee));
+            }
+            credit_fees(storage, &draw_fees)?;
+        }
+        _ => return Err(ContractError::InvalidRoomResult {}),
+    }
+
+    game_rooms_state().save(storage, game_room_id, &game_room.get_finish_state(result))?;
+    Ok(())
+}
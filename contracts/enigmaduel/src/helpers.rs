@@ -1,15 +1,56 @@
-use cosmwasm_std::{to_json_binary, Uint128};
+use cosmwasm_std::{StdResult, Uint128};
 
-pub fn create_key_hash(con_1: String, con_2: String) -> String {
-    to_json_binary(&format!("{}{}", con_1, con_2))
-        .unwrap()
-        .to_string()
+use crate::error::ContractError;
+use crate::state::{FeeConfig, GameRoomsState};
+
+// the contestants of a room in a stable order, (A, B) and (B, A) are the same pair.
+pub fn pair_key(con_1: &str, con_2: &str) -> (String, String) {
+    if con_1 <= con_2 {
+        (con_1.to_string(), con_2.to_string())
+    } else {
+        (con_2.to_string(), con_1.to_string())
+    }
+}
+
+// the index of the winner of a duel, only the two contestants can win it
+pub fn duel_winner(game_room: &GameRoomsState, addr: &str) -> Result<usize, ContractError> {
+    if addr == game_room.contestant1 {
+        Ok(0)
+    } else if addr == game_room.contestant2 {
+        Ok(1)
+    } else {
+        Err(ContractError::NotAContestant {})
+    }
+}
+
+// the share of each contestant of an even duel
+pub fn cal_stake(prize_pool: Uint128) -> Uint128 {
+    prize_pool.checked_div_euclid(Uint128::new(2)).unwrap()
+}
+
+// what the winner of an even duel takes from the loser, the stake minus the fees of both
+pub fn cal_min_required(prize_pool: Uint128, fee_config: &FeeConfig) -> StdResult<Uint128> {
+    let stake = cal_stake(prize_pool);
+    Ok(stake.checked_sub(fee_config.fee(stake) * Uint128::new(2))?)
 }
 
-pub fn cal_min_required(prize_pool: Uint128, fee: Uint128) -> Uint128 {
-    prize_pool
-        .checked_sub(fee * Uint128::new(2))
-        .unwrap()
-        .checked_div_euclid(Uint128::new(2))
-        .unwrap()
+// what each contestant of an even duel takes back out of the pool and pays in fees,
+// in the order of the contestants. `winner` is the index of the winner, none for a draw.
+pub fn even_duel_payouts(
+    prize_pool: Uint128,
+    fee_config: &FeeConfig,
+    winner: Option<usize>,
+) -> StdResult<(Vec<Uint128>, Vec<Uint128>)> {
+    let stake = cal_stake(prize_pool);
+    match winner {
+        Some(winner) => {
+            let mut payouts = vec![Uint128::zero(); 2];
+            payouts[winner] = stake + cal_min_required(prize_pool, fee_config)?;
+            Ok((payouts, vec![fee_config.fee(stake); 2]))
+        }
+        None => {
+            let draw_fee = fee_config.draw_fee(stake);
+            Ok((vec![stake.checked_sub(draw_fee)?; 2], vec![draw_fee; 2]))
+        }
+    }
 }
@@ -0,0 +1,68 @@
+use cosmwasm_std::{attr, to_json_binary, Binary, DepsMut, Env, MessageInfo, Response, Uint128};
+use sha2::{Digest, Sha256};
+
+use crate::contract::execute::{ensure_valid_stake, room_deadline, save_new_game_room};
+use crate::error::ContractError;
+use crate::fees::load_fee_config;
+use crate::msg::{CreateGameRoomResp, GameRoomStatus};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::state::GameRoomsState;
+
+// the creator joins right away, the second seat stays free until someone joins with the secret.
+// like any other room nothing is locked until both of the contestants joined.
+pub fn execute_create_private_room(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stake: Uint128,
+    secret_hash: Binary,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    if secret_hash.len() != 32 {
+        return Err(ContractError::InvalidRoomSecret {});
+    }
+    ensure_valid_stake(deps.storage, stake)?;
+
+    let game_room = GameRoomsState {
+        contestant1: info.sender.to_string(),
+        contestant2: String::new(),
+        prize_pool: stake + stake,
+        status: GameRoomStatus::Pending {
+            joined: vec![info.sender.to_string()],
+        },
+        created_at: env.block.time,
+        created_height: env.block.height,
+        deadline: room_deadline(deps.storage, &env.block)?,
+        secret_hash: Some(secret_hash),
+        extra_contestants: vec![],
+        payout_table: vec![],
+        series: None,
+        stakes: vec![],
+        payout_policy: Default::default(),
+        teams: vec![],
+        fee_config: Some(load_fee_config(deps.storage, None)?),
+    };
+    let game_room_id = save_new_game_room(deps.storage, &game_room)?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&CreateGameRoomResp(game_room_id))?)
+        .add_attributes(vec![
+            attr("action", "create_private_room"),
+            attr("room_id", game_room_id.to_string()),
+            attr("creator", info.sender),
+            attr("stake", stake),
+        ]))
+}
+
+pub fn verify_room_secret(
+    secret_hash: &Binary,
+    secret: Option<Binary>,
+) -> Result<(), ContractError> {
+    match secret {
+        Some(secret) if Sha256::digest(secret.as_slice()).as_slice() == secret_hash.as_slice() => {
+            Ok(())
+        }
+        _ => Err(ContractError::InvalidRoomSecret {}),
+    }
+}
@@ -0,0 +1,90 @@
+use cosmwasm_std::{Deps, Order, StdResult};
+use cw_storage_plus::Bound;
+
+use crate::msg::{LeaderboardEntry, LeaderboardKind, LeaderboardResp};
+use crate::ratings::load_rating;
+use crate::state::{player_stats, ratings};
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+// the players with the highest value first, `start_after` is the last player of the previous page.
+pub fn query_leaderboard(
+    deps: Deps,
+    kind: LeaderboardKind,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<LeaderboardResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let stats = player_stats();
+    let after_stats = match &start_after {
+        Some(player) => stats.may_load(deps.storage, player)?.unwrap_or_default(),
+        None => Default::default(),
+    };
+
+    let players = match kind {
+        LeaderboardKind::Rating => {
+            let end = match start_after {
+                Some(player) => Some(Bound::exclusive((
+                    load_rating(deps.storage, &player)?,
+                    player,
+                ))),
+                None => None,
+            };
+            ratings()
+                .idx
+                .rating
+                .keys(deps.storage, None, end, Order::Descending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?
+        }
+        LeaderboardKind::NetWinnings => stats
+            .idx
+            .net_profit
+            .keys(
+                deps.storage,
+                None,
+                start_after.map(|player| Bound::exclusive((after_stats.net_profit.i128(), player))),
+                Order::Descending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+        LeaderboardKind::Wins => stats
+            .idx
+            .wins
+            .keys(
+                deps.storage,
+                None,
+                start_after.map(|player| Bound::exclusive((after_stats.wins, player))),
+                Order::Descending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+        LeaderboardKind::Volume => stats
+            .idx
+            .total_wagered
+            .keys(
+                deps.storage,
+                None,
+                start_after
+                    .map(|player| Bound::exclusive((after_stats.total_wagered.u128(), player))),
+                Order::Descending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    let entries = players
+        .into_iter()
+        .map(|player| {
+            Ok(LeaderboardEntry {
+                rating: load_rating(deps.storage, &player)?,
+                stats: stats.may_load(deps.storage, &player)?.unwrap_or_default(),
+                player,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(LeaderboardResp { entries })
+}
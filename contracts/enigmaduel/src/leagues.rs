@@ -0,0 +1,568 @@
+use cosmwasm_std::{
+    attr, Addr, BlockInfo, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage,
+    Uint128,
+};
+use cw_storage_plus::Bound;
+
+use crate::contract::execute::{
+    ensure_valid_stake, lock_stake, room_deadline, save_new_game_room, unlock_stake,
+};
+use crate::error::ContractError;
+use crate::fees::{credit_fees, load_fee_config};
+use crate::helpers::duel_winner;
+use crate::msg::{
+    GameRoomStatus, LeagueFixtureResp, LeagueFixturesResp, LeagueResp, LeagueStandingEntry,
+    LeagueStandingsResp, ListLeaguesResp, Role,
+};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::ratings::update_ratings;
+use crate::roles::ensure_role;
+use crate::state::{
+    game_rooms_state, Balance, GameRoomsState, League, LeagueFixture, LeagueStanding, LeagueStatus,
+    BALANCES, LEAGUES, LEAGUES_COUNT, LEAGUE_DIVISIONS, LEAGUE_FIXTURES, LEAGUE_ROOMS,
+    LEAGUE_STANDINGS,
+};
+use crate::stats::record_stakeless_match;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub const MAX_DIVISIONS: u32 = 4;
+pub const MIN_DIVISION_SIZE: u32 = 2;
+pub const MAX_DIVISION_SIZE: u32 = 8;
+const WIN_POINTS: u32 = 3;
+const DRAW_POINTS: u32 = 1;
+// the payout table shares add up to the whole pool
+const TOTAL_SHARES: u16 = 10_000;
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_league(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    entry_fee: Uint128,
+    divisions: u32,
+    division_size: u32,
+    movers: u32,
+    payout_table: Vec<u16>,
+) -> Result<Response, ContractError> {
+    // sender must be a game operator
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    if !(1..=MAX_DIVISIONS).contains(&divisions)
+        || !(MIN_DIVISION_SIZE..=MAX_DIVISION_SIZE).contains(&division_size)
+        || movers * 2 > division_size
+    {
+        return Err(ContractError::InvalidLeagueSize {
+            max_divisions: MAX_DIVISIONS,
+            max_players: MAX_DIVISION_SIZE,
+        });
+    }
+    if payout_table.is_empty()
+        || payout_table.len() as u32 > division_size
+        || payout_table.iter().map(|share| *share as u32).sum::<u32>() != TOTAL_SHARES as u32
+    {
+        return Err(ContractError::InvalidPayoutTable {});
+    }
+    ensure_valid_stake(deps.storage, entry_fee)?;
+    // the players register for the fee of today, a later config update doesn't touch the league
+    let fee_config = load_fee_config(deps.storage, None)?;
+    let deadline = room_deadline(deps.storage, &env.block)?;
+
+    let league_id = LEAGUES_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    LEAGUES_COUNT.save(deps.storage, &league_id)?;
+    LEAGUES.save(
+        deps.storage,
+        league_id,
+        &League {
+            name,
+            entry_fee,
+            divisions,
+            division_size,
+            movers,
+            payout_table,
+            season: 1,
+            open_fixtures: 0,
+            status: LeagueStatus::Registration {},
+            fee_config: Some(fee_config),
+            deadline: Some(deadline),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_league"),
+        attr("league_id", league_id.to_string()),
+        attr("entry_fee", entry_fee),
+        attr("divisions", divisions.to_string()),
+        attr("division_size", division_size.to_string()),
+    ]))
+}
+
+// the returning players keep the division they earned last season while it has room,
+// everyone else takes the highest division with a free seat.
+pub fn execute_register_for_league(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    league_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let mut league = LEAGUES.load(deps.storage, league_id)?;
+    if league.status != (LeagueStatus::Registration {}) {
+        return Err(ContractError::LeagueNotOpen {});
+    }
+    let player = info.sender.to_string();
+    if LEAGUE_STANDINGS.has(deps.storage, (league_id, league.season, &player)) {
+        return Err(ContractError::AlreadyRegistered {});
+    }
+
+    let mut sizes = vec![0; league.divisions as usize];
+    for standing in season_standings(deps.storage, league_id, league.season)? {
+        sizes[standing.1.division as usize] += 1;
+    }
+    let has_room = |division: &u32| sizes[*division as usize] < league.division_size;
+    let division = LEAGUE_DIVISIONS
+        .may_load(deps.storage, (league_id, &player))?
+        .filter(has_room)
+        .or_else(|| (0..league.divisions).find(has_room))
+        .ok_or(ContractError::LeagueFull {})?;
+
+    // the entry fee stays locked until the season is over
+    lock_stake(deps.storage, &info.sender, league.entry_fee)?;
+    league.deadline = Some(room_deadline(deps.storage, &env.block)?);
+    LEAGUES.save(deps.storage, league_id, &league)?;
+    LEAGUE_DIVISIONS.save(deps.storage, (league_id, &player), &division)?;
+    LEAGUE_STANDINGS.save(
+        deps.storage,
+        (league_id, league.season, &player),
+        &LeagueStanding {
+            division,
+            ..Default::default()
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_for_league"),
+        attr("league_id", league_id.to_string()),
+        attr("season", league.season.to_string()),
+        attr("division", division.to_string()),
+        attr("player", player),
+    ]))
+}
+
+// every player of a division meets every other player once
+pub fn execute_start_league_season(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    league_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let mut league = LEAGUES.load(deps.storage, league_id)?;
+    if league.status != (LeagueStatus::Registration {}) {
+        return Err(ContractError::LeagueNotOpen {});
+    }
+    let standings = season_standings(deps.storage, league_id, league.season)?;
+    if standings.len() < 2 {
+        return Err(ContractError::NotEnoughPlayers {});
+    }
+
+    let deadline = room_deadline(deps.storage, &env.block)?;
+    for division in 0..league.divisions {
+        let players: Vec<&String> = standings
+            .iter()
+            .filter(|(_, standing)| standing.division == division)
+            .map(|(player, _)| player)
+            .collect();
+        for (i, home) in players.iter().enumerate() {
+            for away in &players[i + 1..] {
+                let game_room = GameRoomsState {
+                    contestant1: home.to_string(),
+                    contestant2: away.to_string(),
+                    // the entry fees are the stakes, the room itself holds nothing
+                    prize_pool: Uint128::zero(),
+                    status: GameRoomStatus::Started {},
+                    created_at: env.block.time,
+                    created_height: env.block.height,
+                    deadline,
+                    secret_hash: None,
+                    extra_contestants: vec![],
+                    payout_table: vec![],
+                    series: None,
+                    stakes: vec![],
+                    payout_policy: Default::default(),
+                    teams: vec![],
+                    fee_config: None,
+                };
+                let game_room_id = save_new_game_room(deps.storage, &game_room)?;
+                LEAGUE_FIXTURES.save(
+                    deps.storage,
+                    (league_id, league.season, game_room_id),
+                    &division,
+                )?;
+                LEAGUE_ROOMS.save(
+                    deps.storage,
+                    game_room_id,
+                    &LeagueFixture {
+                        league_id,
+                        season: league.season,
+                        division,
+                    },
+                )?;
+                league.open_fixtures += 1;
+            }
+        }
+    }
+    league.status = LeagueStatus::Running {};
+    league.deadline = Some(deadline);
+    LEAGUES.save(deps.storage, league_id, &league)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "start_league_season"),
+        attr("league_id", league_id.to_string()),
+        attr("season", league.season.to_string()),
+        attr("fixtures", league.open_fixtures.to_string()),
+    ]))
+}
+
+// the rooms still waiting for a result expire and count for nobody
+pub fn execute_end_league_season(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    league_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomSettlement)?;
+
+    let league = LEAGUES.load(deps.storage, league_id)?;
+    if league.status != (LeagueStatus::Running {}) {
+        return Err(ContractError::LeagueNotRunning {});
+    }
+
+    expire_open_fixtures(deps.storage, league_id, league.season)?;
+    let season = league.season;
+    end_league_season(deps.storage, &env.block, league_id, league)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "end_league_season"),
+        attr("league_id", league_id.to_string()),
+        attr("season", season.to_string()),
+    ]))
+}
+
+// gives every player of the season their entry fee back, the rooms still waiting
+// for a result expire and the league takes no more registrations.
+// anyone can cancel a league the operators stopped running past its deadline.
+pub fn execute_cancel_league(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    league_id: u64,
+) -> Result<Response, ContractError> {
+    let mut league = LEAGUES.load(deps.storage, league_id)?;
+    let past_deadline = matches!(league.deadline, Some(deadline) if env.block.time >= deadline);
+    if !past_deadline {
+        ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    }
+    match league.status {
+        LeagueStatus::Registration {} | LeagueStatus::Running {} => {}
+        _ => return Err(ContractError::LeagueNotRunning {}),
+    }
+
+    for (player, _) in season_standings(deps.storage, league_id, league.season)? {
+        unlock_stake(deps.storage, &Addr::unchecked(player), league.entry_fee)?;
+    }
+    expire_open_fixtures(deps.storage, league_id, league.season)?;
+    league.open_fixtures = 0;
+    league.status = LeagueStatus::Cancelled {};
+    LEAGUES.save(deps.storage, league_id, &league)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_league"),
+        attr("league_id", league_id.to_string()),
+        attr("season", league.season.to_string()),
+    ]))
+}
+
+pub fn finish_league_fixture(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    game_room_id: u64,
+    game_room: &GameRoomsState,
+    fixture: LeagueFixture,
+    result: GameRoomStatus,
+) -> Result<Response, ContractError> {
+    let mut league = LEAGUES.load(storage, fixture.league_id)?;
+    if league.status != (LeagueStatus::Running {}) || league.season != fixture.season {
+        return Err(ContractError::LeagueNotRunning {});
+    }
+
+    let winner = match &result {
+        GameRoomStatus::Win { addr } => Some(duel_winner(game_room, addr)?),
+        GameRoomStatus::Draw {} => None,
+        _ => return Err(ContractError::InvalidRoomResult {}),
+    };
+    let home = game_room.contestant1.as_str();
+    let away = game_room.contestant2.as_str();
+    let (home_points, away_points) = match winner {
+        Some(0) => (WIN_POINTS, 0),
+        Some(_) => (0, WIN_POINTS),
+        None => (DRAW_POINTS, DRAW_POINTS),
+    };
+    for (player, points, other_points) in [
+        (home, home_points, away_points),
+        (away, away_points, home_points),
+    ] {
+        LEAGUE_STANDINGS.update(
+            storage,
+            (fixture.league_id, fixture.season, player),
+            |standing| -> StdResult<_> {
+                let mut standing = standing.unwrap_or_default();
+                standing.points += points;
+                standing.played += 1;
+                match points.cmp(&other_points) {
+                    std::cmp::Ordering::Greater => standing.wins += 1,
+                    std::cmp::Ordering::Equal => standing.draws += 1,
+                    std::cmp::Ordering::Less => standing.losses += 1,
+                }
+                Ok(standing)
+            },
+        )?;
+    }
+
+    record_stakeless_match(storage, block, game_room_id, game_room, winner)?;
+    update_ratings(storage, block, game_room_id, game_room, &result)?;
+    game_rooms_state().save(storage, game_room_id, &game_room.get_finish_state(result))?;
+
+    // the season is over once the last fixture is played
+    league.open_fixtures -= 1;
+    league.deadline = Some(room_deadline(storage, block)?);
+    if league.open_fixtures == 0 {
+        end_league_season(storage, block, fixture.league_id, league)?;
+    } else {
+        LEAGUES.save(storage, fixture.league_id, &league)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "finish_game_room"),
+        attr("room_id", game_room_id.to_string()),
+        attr("league_id", fixture.league_id.to_string()),
+        attr("season", fixture.season.to_string()),
+        attr("division", fixture.division.to_string()),
+    ]))
+}
+
+// pays out every division, then swaps the bottom of each division with the top of the one below
+fn end_league_season(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    league_id: u64,
+    mut league: League,
+) -> StdResult<()> {
+    let fee = match &league.fee_config {
+        Some(fee_config) => fee_config.fee(league.entry_fee),
+        None => load_fee_config(storage, None)?.fee(league.entry_fee),
+    };
+    let standings = season_standings(storage, league_id, league.season)?;
+    let tables: Vec<Vec<String>> = (0..league.divisions)
+        .map(|division| {
+            division_table(&standings, division)
+                .into_iter()
+                .map(|(player, _)| player.clone())
+                .collect()
+        })
+        .collect();
+
+    let mut total_fee = Uint128::zero();
+    for table in &tables {
+        if table.is_empty() {
+            continue;
+        }
+        let players = Uint128::new(table.len() as u128);
+        let division_fee = fee * players;
+        let pool = (league.entry_fee * players).checked_sub(division_fee)?;
+        total_fee += division_fee;
+
+        // the shares past the last position and the rounding dust go to the first place
+        let mut payouts: Vec<Uint128> = table
+            .iter()
+            .enumerate()
+            .map(|(position, _)| match league.payout_table.get(position) {
+                Some(share) => pool.multiply_ratio(*share, TOTAL_SHARES),
+                None => Uint128::zero(),
+            })
+            .collect();
+        let paid = payouts.iter().sum::<Uint128>();
+        payouts[0] += pool - paid;
+
+        for (player, payout) in table.iter().zip(payouts) {
+            BALANCES.update(
+                storage,
+                &Addr::unchecked(player),
+                |balance: Option<Balance>| -> StdResult<_> {
+                    Ok(balance
+                        .unwrap_or_default()
+                        .unlock_and_decrease(league.entry_fee, league.entry_fee)
+                        .total_increase(payout))
+                },
+            )?;
+        }
+    }
+    credit_fees(
+        storage,
+        &tables
+            .iter()
+            .flatten()
+            .map(|player| (player.as_str(), fee))
+            .collect::<Vec<_>>(),
+    )?;
+
+    // at most half of a division moves either way so nobody is promoted and relegated at once
+    let movers = league.movers as usize;
+    for division in 1..tables.len() {
+        let upper = &tables[division - 1];
+        let lower = &tables[division];
+        let count = movers.min(upper.len() / 2).min(lower.len() / 2);
+        for player in &upper[upper.len() - count..] {
+            LEAGUE_DIVISIONS.save(storage, (league_id, player), &(division as u32))?;
+        }
+        for player in &lower[..count] {
+            LEAGUE_DIVISIONS.save(storage, (league_id, player), &(division as u32 - 1))?;
+        }
+    }
+
+    league.season += 1;
+    league.open_fixtures = 0;
+    league.status = LeagueStatus::Registration {};
+    league.deadline = Some(room_deadline(storage, block)?);
+    LEAGUES.save(storage, league_id, &league)
+}
+
+fn expire_open_fixtures(storage: &mut dyn Storage, league_id: u64, season: u32) -> StdResult<()> {
+    let game_room_ids = LEAGUE_FIXTURES
+        .prefix((league_id, season))
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for game_room_id in game_room_ids {
+        let game_room = game_rooms_state().load(storage, game_room_id)?;
+        if game_room.status == (GameRoomStatus::Started {}) {
+            game_rooms_state().save(
+                storage,
+                game_room_id,
+                &game_room.get_finish_state(GameRoomStatus::Expired {}),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn season_standings(
+    storage: &dyn Storage,
+    league_id: u64,
+    season: u32,
+) -> StdResult<Vec<(String, LeagueStanding)>> {
+    LEAGUE_STANDINGS
+        .prefix((league_id, season))
+        .range(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+// the most points first, the wins and then the address break the ties
+fn division_table(
+    standings: &[(String, LeagueStanding)],
+    division: u32,
+) -> Vec<&(String, LeagueStanding)> {
+    let mut table: Vec<_> = standings
+        .iter()
+        .filter(|(_, standing)| standing.division == division)
+        .collect();
+    table.sort_by_key(|(_, standing)| std::cmp::Reverse((standing.points, standing.wins)));
+    table
+}
+
+pub fn query_league(deps: Deps, league_id: u64) -> StdResult<League> {
+    LEAGUES.load(deps.storage, league_id)
+}
+
+pub fn query_leagues(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListLeaguesResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let leagues = LEAGUES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(league_id, league)| LeagueResp { league_id, league }))
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListLeaguesResp { leagues })
+}
+
+pub fn query_league_standings(
+    deps: Deps,
+    league_id: u64,
+    season: Option<u32>,
+    division: u32,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<LeagueStandingsResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let season = match season {
+        Some(season) => season,
+        None => LEAGUES.load(deps.storage, league_id)?.season,
+    };
+
+    let standings = season_standings(deps.storage, league_id, season)?;
+    let standings = division_table(&standings, division)
+        .into_iter()
+        .enumerate()
+        .skip(start_after.unwrap_or_default() as usize)
+        .take(limit)
+        .map(|(position, (player, standing))| LeagueStandingEntry {
+            position: position as u32 + 1,
+            player: player.clone(),
+            standing: standing.clone(),
+        })
+        .collect();
+
+    Ok(LeagueStandingsResp { standings })
+}
+
+pub fn query_league_fixtures(
+    deps: Deps,
+    league_id: u64,
+    season: Option<u32>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<LeagueFixturesResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let season = match season {
+        Some(season) => season,
+        None => LEAGUES.load(deps.storage, league_id)?.season,
+    };
+
+    let fixtures = LEAGUE_FIXTURES
+        .prefix((league_id, season))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(game_room_id, division)| LeagueFixtureResp {
+                game_room_id,
+                division,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(LeagueFixturesResp { fixtures })
+}
@@ -1,8 +1,26 @@
+pub mod challenges;
+pub mod consent;
 pub mod contract;
+pub mod enumerable;
 mod error;
+pub mod fees;
+pub mod handicap;
 pub mod helpers;
+pub mod invites;
+pub mod leaderboard;
+pub mod leagues;
+pub mod matchmaking;
 pub mod msg;
+pub mod pause;
+pub mod ranked;
+pub mod ratings;
+pub mod roles;
+pub mod scoring;
+pub mod series;
 pub mod state;
+pub mod stats;
+pub mod teams;
+pub mod tournaments;
 pub mod uint_tests;
 
 pub use crate::error::ContractError;
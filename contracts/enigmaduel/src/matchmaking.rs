@@ -0,0 +1,166 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128,
+};
+use cw_storage_plus::Bound;
+
+use crate::contract::execute::{
+    ensure_valid_stake, lock_stake, room_deadline, save_new_game_room, unlock_stake,
+};
+use crate::error::ContractError;
+use crate::fees::load_fee_config;
+use crate::msg::{
+    CreateGameRoomResp, GameRoomStatus, GetQueueEntryResp, ListQueueResp, QueueEntryResp,
+};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::ratings::load_rating;
+use crate::state::{GameRoomsState, QueueEntry, QueueTicket, QUEUE, QUEUED_PLAYERS, QUEUE_TICKETS};
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+// the number of waiting players checked for a compatible rating on each join.
+const MAX_QUEUE_SCAN: usize = 30;
+
+pub fn execute_join_queue(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stake: Uint128,
+    max_rating_gap: Option<u32>,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let player = info.sender.to_string();
+    if QUEUED_PLAYERS.has(deps.storage, &player) {
+        return Err(ContractError::AlreadyQueued {});
+    }
+    ensure_valid_stake(deps.storage, stake)?;
+    lock_stake(deps.storage, &info.sender, stake)?;
+
+    let rating = load_rating(deps.storage, &player)?;
+    let res = Response::new().add_attributes(vec![
+        attr("action", "join_queue"),
+        attr("player", &player),
+        attr("stake", stake),
+    ]);
+
+    // the oldest waiting player of the tier within both rating bands
+    let opponent = QUEUE
+        .prefix(stake.u128())
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(MAX_QUEUE_SCAN)
+        .find(|item| match item {
+            Ok((_, entry)) => {
+                let gap = rating.abs_diff(entry.rating);
+                max_rating_gap.is_none_or(|max| gap <= max)
+                    && entry.max_rating_gap.is_none_or(|max| gap <= max)
+            }
+            Err(_) => true,
+        })
+        .transpose()?;
+
+    match opponent {
+        Some((ticket, entry)) => {
+            QUEUE.remove(deps.storage, (stake.u128(), ticket));
+            QUEUED_PLAYERS.remove(deps.storage, &entry.player);
+
+            // both stakes are already locked
+            let game_room = GameRoomsState {
+                contestant1: entry.player.clone(),
+                contestant2: player,
+                prize_pool: stake + stake,
+                status: GameRoomStatus::Started {},
+                created_at: env.block.time,
+                created_height: env.block.height,
+                deadline: room_deadline(deps.storage, &env.block)?,
+                secret_hash: None,
+                extra_contestants: vec![],
+                payout_table: vec![],
+                series: None,
+                stakes: vec![],
+                payout_policy: Default::default(),
+                teams: vec![],
+                fee_config: Some(load_fee_config(deps.storage, None)?),
+            };
+            let game_room_id = save_new_game_room(deps.storage, &game_room)?;
+
+            Ok(res
+                .set_data(to_json_binary(&CreateGameRoomResp(game_room_id))?)
+                .add_attribute("opponent", entry.player)
+                .add_attribute("room_id", game_room_id.to_string()))
+        }
+        None => {
+            let ticket = QUEUE_TICKETS.may_load(deps.storage)?.unwrap_or_default() + 1;
+            QUEUE_TICKETS.save(deps.storage, &ticket)?;
+            QUEUE.save(
+                deps.storage,
+                (stake.u128(), ticket),
+                &QueueEntry {
+                    player: player.clone(),
+                    rating,
+                    max_rating_gap,
+                    joined_at: env.block.time,
+                },
+            )?;
+            QUEUED_PLAYERS.save(deps.storage, &player, &QueueTicket { stake, ticket })?;
+
+            Ok(res.add_attribute("ticket", ticket.to_string()))
+        }
+    }
+}
+
+pub fn execute_leave_queue(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let player = info.sender.to_string();
+    let queued = QUEUED_PLAYERS
+        .may_load(deps.storage, &player)?
+        .ok_or(ContractError::NotQueued {})?;
+
+    QUEUE.remove(deps.storage, (queued.stake.u128(), queued.ticket));
+    QUEUED_PLAYERS.remove(deps.storage, &player);
+
+    unlock_stake(deps.storage, &info.sender, queued.stake)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "leave_queue"),
+        attr("player", player),
+        attr("stake", queued.stake),
+    ]))
+}
+
+pub fn query_queue_entry(deps: Deps, player: String) -> StdResult<GetQueueEntryResp> {
+    let entry = match QUEUED_PLAYERS.may_load(deps.storage, &player)? {
+        Some(queued) => Some(QueueEntryResp {
+            stake: queued.stake,
+            ticket: queued.ticket,
+            entry: QUEUE.load(deps.storage, (queued.stake.u128(), queued.ticket))?,
+        }),
+        None => None,
+    };
+    Ok(GetQueueEntryResp { entry })
+}
+
+// the waiting players of the stake tier, the oldest first
+pub fn query_queue(
+    deps: Deps,
+    stake: Uint128,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListQueueResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let entries = QUEUE
+        .prefix(stake.u128())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(ticket, entry)| QueueEntryResp {
+                stake,
+                ticket,
+                entry,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListQueueResp { entries })
+}
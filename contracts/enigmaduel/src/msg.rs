@@ -1,5 +1,11 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Binary, Uint128};
+use cosmwasm_std::{Binary, Timestamp, Uint128};
+use std::fmt;
+
+use crate::state::{
+    Challenge, FeeConfig, FeeLedger, FeeRecipient, GameRoomsState, League, LeagueStanding,
+    MatchRecord, PauseState, PlayerStats, QueueEntry, RatingChange, Tournament, TournamentMatch,
+};
 
 // todo !
 #[cw_serde]
@@ -7,6 +13,17 @@ pub struct InstantiateMsg {
     pub fee: Uint128,
     pub admin: String,
     pub enigma_token_duel: String,
+    // seconds before an unsettled room can be expired, defaults to an hour and can't be under a minute.
+    pub room_timeout: Option<u64>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct MigrateMsg {
+    // the part of the admin balance that is uncollected fees, moved to the fee ledger when upgrading
+    // from a release older than 0.9.0. those releases credited the fees to the admin balance along
+    // with the admin's own deposits and winnings, nothing moves when missing.
+    pub admin_fees: Option<Uint128>,
 }
 
 // executing input and output structs/enums //
@@ -19,11 +36,47 @@ pub struct GameRoomIntiParams {
     // contestant one share + contestant two share + Enigma Duel Fee.
     pub prize_pool: Uint128,
     pub status: GameRoomStatus,
+    // the signed consents of both contestants, without them the room waits for the contestants to join.
+    pub consents: Option<RoomConsents>,
+    // how a scored result splits the pool, winner-takes-all when missing.
+    pub payout_policy: Option<PayoutPolicy>,
+    // the fee schedule of the room, the contract wide one when missing.
+    pub fee_config: Option<FeeConfig>,
+}
+
+#[cw_serde]
+pub struct RoomConsents {
+    // the key the operator gave the room, a consent only starts the room it was signed for.
+    pub room_key: String,
+    pub expires: Timestamp,
+    // secp256k1 signatures over the sha256 of the json encoded `ConsentPayload`.
+    pub contestant1_signature: Binary,
+    pub contestant2_signature: Binary,
+}
+
+// the message each contestant signs off-chain to agree to the stake and the terms of a room.
+#[cw_serde]
+pub struct ConsentPayload {
+    pub contract: String,
+    pub contestant1: String,
+    pub contestant2: String,
+    pub stake: Uint128,
+    pub payout_policy: PayoutPolicy,
+    // the fee schedule the room settles with.
+    pub fee_config: Option<FeeConfig>,
+    pub room_key: String,
+    pub expires: Timestamp,
+}
+
+#[cw_serde]
+pub struct TeamMember {
+    pub addr: String,
+    pub stake: Uint128,
 }
 
 #[cw_serde]
 pub struct GameRoomFinishParams {
-    pub game_room_key: String,
+    pub game_room_id: u64,
     pub result: GameRoomStatus,
 }
 
@@ -35,10 +88,152 @@ pub struct CollectFeesParams {
 
 #[cw_serde]
 pub enum GameRoomStatus {
+    // waiting for the contestants to agree to the stake.
+    Pending { joined: Vec<String> },
     Started {},
     Win { addr: String },
     Draw {},
+    // never settled before the deadline, the stakes were refunded.
+    Expired {},
+    // the final standings of a multi-player room, the winner first.
+    Ranked { ranking: Vec<String> },
+    // the quiz scores of both contestants, settled with the payout policy of the room.
+    Scored { scores: Vec<(String, u32)> },
+    // the winning side of a team room, 1 or 2.
+    TeamWin { team: u8 },
+}
+
+// how a scored duel splits the pool once the fees are taken, equal scores are a draw.
+#[cw_serde]
+pub enum PayoutPolicy {
+    // the best score takes the whole pool
+    WinnerTakesAll {},
+    // every contestant takes the share of the pool their score is of the total
+    Proportional {},
+    // a loser within `margin` points of the winner gets `refund` basis points of their stake back
+    MarginRefund { margin: u32, refund: u16 },
+}
+
+impl Default for PayoutPolicy {
+    fn default() -> Self {
+        Self::WinnerTakesAll {}
+    }
+}
+
+impl fmt::Display for GameRoomStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pending { .. } => write!(f, "pending"),
+            Self::Started {} => write!(f, "started"),
+            Self::Win { addr } => write!(f, "win {}", addr),
+            Self::Draw {} => write!(f, "draw"),
+            Self::Expired {} => write!(f, "expired"),
+            Self::Ranked { .. } => write!(f, "ranked"),
+            Self::Scored { .. } => write!(f, "scored"),
+            Self::TeamWin { team } => write!(f, "team win {}", team),
+        }
+    }
+}
+
+impl GameRoomStatus {
+    pub fn kind(&self) -> GameRoomStatusKind {
+        match self {
+            Self::Pending { .. } => GameRoomStatusKind::Pending,
+            Self::Started {} => GameRoomStatusKind::Started,
+            Self::Win { .. } => GameRoomStatusKind::Win,
+            Self::Draw {} => GameRoomStatusKind::Draw,
+            Self::Expired {} => GameRoomStatusKind::Expired,
+            Self::Ranked { .. } => GameRoomStatusKind::Ranked,
+            Self::Scored { .. } => GameRoomStatusKind::Scored,
+            Self::TeamWin { .. } => GameRoomStatusKind::TeamWin,
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum MatchOutcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum LeaderboardKind {
+    Rating,
+    NetWinnings,
+    Wins,
+    Volume,
+}
+
+// the room status without its details, the rooms are indexed and filtered by it.
+#[cw_serde]
+#[derive(Copy)]
+pub enum GameRoomStatusKind {
+    Pending,
+    Started,
+    Win,
+    Draw,
+    Expired,
+    Ranked,
+    Scored,
+    TeamWin,
+}
+
+impl GameRoomStatusKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Started => "started",
+            Self::Win => "win",
+            Self::Draw => "draw",
+            Self::Expired => "expired",
+            Self::Ranked => "ranked",
+            Self::Scored => "scored",
+            Self::TeamWin => "team_win",
+        }
+    }
+}
+#[cw_serde]
+pub enum Role {
+    // creates and finishes the game rooms, held by the game servers.
+    Operator,
+    // collects the fees.
+    Treasurer,
+    // halts and resumes the operations.
+    Pauser,
+    // can be named as the referrer of the players they bring.
+    Referrer,
+}
+
+impl Role {
+    pub fn all() -> Vec<Role> {
+        vec![
+            Role::Operator,
+            Role::Treasurer,
+            Role::Pauser,
+            Role::Referrer,
+        ]
+    }
+
+    // the storage key of the role
+    pub fn key(&self) -> &'static str {
+        match self {
+            Role::Operator => "operator",
+            Role::Treasurer => "treasurer",
+            Role::Pauser => "pauser",
+            Role::Referrer => "referrer",
+        }
+    }
 }
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
 #[cw_serde]
 pub struct SendFrom {
     pub owner: String,
@@ -60,11 +255,12 @@ pub enum UpdateBalanceMode {
     },
 }
 
-impl UpdateBalanceMode {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for UpdateBalanceMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Deposit { user, amount } => {
-                format!(
+                write!(
+                    f,
                     "Deposit {} tokens for user {} ",
                     amount,
                     user.clone().unwrap()
@@ -75,7 +271,8 @@ impl UpdateBalanceMode {
                 amount,
                 receiver,
             } => {
-                format!(
+                write!(
+                    f,
                     "withdraw {} tokens for user {} from {} ",
                     amount,
                     receiver,
@@ -103,6 +300,173 @@ pub enum ExecuteMsg {
         collect_fees_params: CollectFeesParams,
     },
     Receive(Cw20ReceiveMsg),
+    // admin rotation, the proposed admin must accept before taking over.
+    ProposeAdmin {
+        new_admin: String,
+    },
+    AcceptAdmin {},
+    RenounceAdmin {},
+    // role registry, managed by the admin.
+    GrantRole {
+        role: Role,
+        addr: String,
+    },
+    RevokeRole {
+        role: Role,
+        addr: String,
+    },
+    // circuit breaker, managed by the pausers.
+    SetPauseState {
+        pause_state: PauseState,
+    },
+    WithdrawOnly {},
+    // player consent, the stake is locked once both contestants joined.
+    // the secret is the invite code of a private room.
+    JoinRoom {
+        game_room_id: u64,
+        secret: Option<Binary>,
+    },
+    // a multi-player room, the pool is split across the ranking with the payout table.
+    CreateRankedRoom {
+        contestants: Vec<String>,
+        stake: Uint128,
+        payout_table: Vec<u16>,
+    },
+    // a single-elimination tournament, the pool is split with the payout table.
+    CreateTournament {
+        name: String,
+        entry_fee: Uint128,
+        max_players: u32,
+        payout_table: Vec<u16>,
+    },
+    // locks the entry fee of the sender until the tournament is over.
+    RegisterForTournament {
+        tournament_id: u64,
+    },
+    // draws the bracket and opens the rooms of the first round.
+    StartTournament {
+        tournament_id: u64,
+    },
+    // gives the entry fees back, the unplayed matches expire.
+    // callable by anyone once the tournament passed its deadline.
+    CancelTournament {
+        tournament_id: u64,
+    },
+    // a duel where each contestant locks their own stake, e.g. 2:1 for the stronger player.
+    CreateHandicapRoom {
+        contestant1: String,
+        contestant2: String,
+        stake1: Uint128,
+        stake2: Uint128,
+    },
+    // a team quiz, every member locks their own stake and the winning team shares
+    // the stakes of the losing one pro rata to what they staked.
+    CreateTeamRoom {
+        team1: Vec<TeamMember>,
+        team2: Vec<TeamMember>,
+    },
+    // a duel played over several games, the stake is locked once for all of them.
+    // the first to win more than half of the games takes the series, otherwise the player
+    // with more wins once all the games are played. it's a draw only when the wins are level.
+    // the room can only be expired once no game was reported for a whole room timeout.
+    CreateSeriesRoom {
+        contestant1: String,
+        contestant2: String,
+        stake: Uint128,
+        best_of: u32,
+    },
+    // the result of the next game of a series, a win or a draw.
+    // the room settles once the series is decided.
+    ReportSeriesGame {
+        game_room_id: u64,
+        result: GameRoomStatus,
+    },
+    // a seasonal round-robin league, each division pool is split with the payout table.
+    CreateLeague {
+        name: String,
+        entry_fee: Uint128,
+        divisions: u32,
+        division_size: u32,
+        movers: u32,
+        payout_table: Vec<u16>,
+    },
+    // locks the entry fee of the sender until the season is over.
+    RegisterForLeague {
+        league_id: u64,
+    },
+    // opens a room for every pairing within each division.
+    StartLeagueSeason {
+        league_id: u64,
+    },
+    // pays the divisions out and moves the players between them, the unplayed rooms expire.
+    EndLeagueSeason {
+        league_id: u64,
+    },
+    // gives the entry fees of the season back, the unplayed rooms expire.
+    // callable by anyone once the league passed its deadline.
+    CancelLeague {
+        league_id: u64,
+    },
+    // a room the sender plays against whoever joins with the preimage of the hash.
+    CreatePrivateRoom {
+        stake: Uint128,
+        secret_hash: Binary,
+    },
+    // the secp256k1 public key used to verify the off-chain consents of the sender.
+    RegisterConsentKey {
+        pubkey: Binary,
+    },
+    // refunds the stakes of a room that wasn't settled before its deadline, callable by anyone.
+    // tournament matches and league fixtures don't expire,
+    // anyone can cancel their tournament or league past its deadline instead.
+    ExpireGameRoom {
+        game_room_id: u64,
+    },
+    // seconds before the rooms created from now on can be expired, at least a minute.
+    UpdateRoomTimeout {
+        room_timeout: u64,
+    },
+    // the fee schedule of the rooms created from now on, the existing rooms keep theirs.
+    UpdateConfig {
+        fee_config: FeeConfig,
+    },
+    // replaces how the fees are split, an empty list sends every fee to the ledger again.
+    UpdateFeeRecipients {
+        recipients: Vec<FeeRecipient>,
+    },
+    // burns the burn shares of the fees collected so far.
+    BurnFees {},
+    // the sender names who referred them, once. only the holders of the referrer role can be named.
+    SetReferrer {
+        referrer: String,
+    },
+    // how far a single duel moves the elo ratings.
+    UpdateEloKFactor {
+        k_factor: u32,
+    },
+    // locks the stake and waits for an opponent of the same stake,
+    // the room starts as soon as a compatible player joins.
+    JoinQueue {
+        stake: Uint128,
+        max_rating_gap: Option<u32>,
+    },
+    // unlocks the stake of the waiting sender.
+    LeaveQueue {},
+    // locks the stake until the challenge is accepted, cancelled or expired,
+    // anyone can accept it when the opponent is missing.
+    CreateChallenge {
+        stake: Uint128,
+        opponent: Option<String>,
+        expires: Timestamp,
+    },
+    // locks the same stake and starts the room.
+    AcceptChallenge {
+        challenge_id: u64,
+    },
+    // gives the stake back, anyone can cancel an expired challenge.
+    CancelChallenge {
+        challenge_id: u64,
+    },
 }
 
 // output structs
@@ -111,7 +475,10 @@ pub enum ExecuteMsg {
 pub struct BalanceChangeResp(pub Uint128); // the new balance.
 
 #[cw_serde]
-pub struct CreateGameRoomResp(pub i64); // the game room id.
+pub struct CreateGameRoomResp(pub u64); // the game room id.
+
+#[cw_serde]
+pub struct CreateChallengeResp(pub u64); // the challenge id.
 
 // executing input and output structs/messages //
 
@@ -122,13 +489,159 @@ pub enum QueryMsg {
     GetUserBalance { user: String },
     #[returns(GetUserBalanceResp)]
     GetUserLockedBalance { user: String },
-    #[returns(GameRoomStatus)]
-    GetGameRoomState { game_room_key: String },
+    #[returns(GameRoomsState)]
+    GetGameRoomState { game_room_id: u64 },
+    // the rooms of a pair in both orders, oldest first
+    #[returns(ListGameRoomsResp)]
+    ListRoomsByPair {
+        contestant1: String,
+        contestant2: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // every room, optionally only the ones in the given status
+    #[returns(ListGameRoomsResp)]
+    ListGameRooms {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        status: Option<GameRoomStatusKind>,
+    },
+    // the rooms the player is a contestant of, on either side, in a team or among the ranked players
+    #[returns(ListGameRoomsResp)]
+    ListRoomsByPlayer {
+        player: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // the pending and started rooms
+    #[returns(ListGameRoomsResp)]
+    ListActiveRooms {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // what the treasurers can still collect
     #[returns(GetCollectedFeesResp)]
     GetCollectedFees {},
+    #[returns(FeeLedger)]
+    GetFeeLedger {},
     // TVL is the contract balance
+    // the id of the last created room, so every room ever created including the tournament
+    // matches and the league fixtures. the rooms numbered by the 0.7.0 migration took the ids
+    // following the old count, which counts them a second time.
     #[returns(GetTotalGamesResp)]
     GetTotalGames {},
+    #[returns(GetAdminResp)]
+    GetAdmin {},
+    #[returns(ListRoleMembersResp)]
+    ListRoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(GetUserRolesResp)]
+    GetUserRoles { user: String },
+    #[returns(GetPauseStateResp)]
+    GetPauseState {},
+    #[returns(GetConsentKeyResp)]
+    GetConsentKey { user: String },
+    #[returns(GetRoomTimeoutResp)]
+    GetRoomTimeout {},
+    // the totals of every room the player settled, the tournament and league matches count no stake
+    #[returns(GetPlayerStatsResp)]
+    GetPlayerStats { player: String },
+    // the settled rooms of the player, the most recent first
+    #[returns(ListPlayerHistoryResp)]
+    ListPlayerHistory {
+        player: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(GetRatingResp)]
+    GetRating { player: String },
+    // the rating updates of the player, the most recent first
+    #[returns(ListRatingHistoryResp)]
+    ListRatingHistory {
+        player: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(ConfigResp)]
+    GetConfig {},
+    // the fee recipients with what each one was credited so far
+    #[returns(ListFeeRecipientsResp)]
+    ListFeeRecipients {},
+    #[returns(GetReferrerResp)]
+    GetReferrer { player: String },
+    // what the contestants of a duel would lock, pay and take home with the current fees.
+    // only the even duels of two contestants: the ranked and team rooms fail with
+    // `UnsupportedRoomKind`, the handicap and series rooms aren't covered either.
+    #[returns(SimulateRoomResp)]
+    SimulateRoom {
+        amount: SimulationAmount,
+        contestants: Vec<String>,
+    },
+    #[returns(GetEloKFactorResp)]
+    GetEloKFactor {},
+    // the top players by the given value, the highest first
+    #[returns(LeaderboardResp)]
+    Leaderboard {
+        kind: LeaderboardKind,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(GetQueueEntryResp)]
+    GetQueueEntry { player: String },
+    // the players waiting with the given stake, the oldest first
+    #[returns(ListQueueResp)]
+    ListQueue {
+        stake: Uint128,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(Challenge)]
+    GetChallenge { challenge_id: u64 },
+    #[returns(Tournament)]
+    GetTournament { tournament_id: u64 },
+    #[returns(GetTournamentBracketResp)]
+    GetTournamentBracket { tournament_id: u64 },
+    #[returns(ListTournamentsResp)]
+    ListTournaments {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(SeriesScoreResp)]
+    GetSeriesScore { game_room_id: u64 },
+    #[returns(League)]
+    GetLeague { league_id: u64 },
+    #[returns(ListLeaguesResp)]
+    ListLeagues {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // the points table of a division, the current season when missing.
+    // `start_after` is the position of the last row of the previous page.
+    #[returns(LeagueStandingsResp)]
+    LeagueStandings {
+        league_id: u64,
+        season: Option<u32>,
+        division: u32,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    // the rooms of a season in every division, the current season when missing.
+    #[returns(LeagueFixturesResp)]
+    ListLeagueFixtures {
+        league_id: u64,
+        season: Option<u32>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // the open challenges, the oldest first
+    #[returns(ListChallengesResp)]
+    ListChallenges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -144,6 +657,238 @@ pub struct GetCollectedFeesResp {
 pub struct GetTotalGamesResp {
     pub total_games: i64,
 }
+#[cw_serde]
+pub struct GetAdminResp {
+    pub admin: Option<String>,
+    pub pending_admin: Option<String>,
+}
+
+#[cw_serde]
+pub struct ListRoleMembersResp {
+    pub members: Vec<String>,
+}
+#[cw_serde]
+pub struct GetUserRolesResp {
+    pub roles: Vec<Role>,
+}
+
+#[cw_serde]
+pub struct GetPauseStateResp {
+    pub pause_state: PauseState,
+    pub withdraw_only: bool,
+}
+
+#[cw_serde]
+pub struct GetConsentKeyResp {
+    pub pubkey: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct GetRoomTimeoutResp {
+    pub room_timeout: u64,
+}
+
+#[cw_serde]
+pub struct GetPlayerStatsResp {
+    pub stats: PlayerStats,
+}
+
+#[cw_serde]
+pub struct PlayerMatchResp {
+    pub game_room_id: u64,
+    pub record: MatchRecord,
+}
+#[cw_serde]
+pub struct ListPlayerHistoryResp {
+    pub matches: Vec<PlayerMatchResp>,
+}
+
+#[cw_serde]
+pub struct GetRatingResp {
+    pub rating: u32,
+}
+
+#[cw_serde]
+pub struct ConfigResp {
+    pub fee_config: FeeConfig,
+}
+
+#[cw_serde]
+pub struct FeeRecipientResp {
+    pub recipient: FeeRecipient,
+    pub accrued: Uint128,
+}
+
+#[cw_serde]
+pub struct ListFeeRecipientsResp {
+    pub recipients: Vec<FeeRecipientResp>,
+    pub pending_burn: Uint128,
+}
+
+#[cw_serde]
+pub struct GetReferrerResp {
+    pub referrer: Option<String>,
+}
+
+// the room to simulate is given either by its prize pool or by the stake of each contestant
+#[cw_serde]
+pub enum SimulationAmount {
+    PrizePool { amount: Uint128 },
+    Stake { amount: Uint128 },
+}
+
+#[cw_serde]
+pub struct SimulatedContestant {
+    pub addr: String,
+    pub required_lock: Uint128,
+    pub available: Uint128,
+    // the room can only start when every contestant has more than the required lock available
+    pub sufficient: bool,
+}
+
+#[cw_serde]
+pub struct SimulatedOutcome {
+    pub result: GameRoomStatus,
+    // what each contestant takes back out of the pool in the order of `contestants`
+    pub payouts: Vec<Uint128>,
+    // the fees of every contestant together
+    pub fees: Uint128,
+}
+
+#[cw_serde]
+pub struct SimulateRoomResp {
+    pub prize_pool: Uint128,
+    pub stake: Uint128,
+    // what each contestant pays on a decided room and on a draw
+    pub fee: Uint128,
+    pub draw_fee: Uint128,
+    pub contestants: Vec<SimulatedContestant>,
+    pub outcomes: Vec<SimulatedOutcome>,
+}
+
+#[cw_serde]
+pub struct GetEloKFactorResp {
+    pub k_factor: u32,
+}
+
+#[cw_serde]
+pub struct RatingChangeResp {
+    pub game_room_id: u64,
+    pub change: RatingChange,
+}
+#[cw_serde]
+pub struct ListRatingHistoryResp {
+    pub changes: Vec<RatingChangeResp>,
+}
+
+#[cw_serde]
+pub struct LeaderboardEntry {
+    pub player: String,
+    pub rating: u32,
+    pub stats: PlayerStats,
+}
+#[cw_serde]
+pub struct LeaderboardResp {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[cw_serde]
+pub struct QueueEntryResp {
+    pub stake: Uint128,
+    pub ticket: u64,
+    pub entry: QueueEntry,
+}
+#[cw_serde]
+pub struct GetQueueEntryResp {
+    pub entry: Option<QueueEntryResp>,
+}
+#[cw_serde]
+pub struct ListQueueResp {
+    pub entries: Vec<QueueEntryResp>,
+}
+
+#[cw_serde]
+pub struct ChallengeResp {
+    pub challenge_id: u64,
+    pub challenge: Challenge,
+}
+#[cw_serde]
+pub struct ListChallengesResp {
+    pub challenges: Vec<ChallengeResp>,
+}
+
+#[cw_serde]
+pub struct TournamentResp {
+    pub tournament_id: u64,
+    pub tournament: Tournament,
+}
+#[cw_serde]
+pub struct ListTournamentsResp {
+    pub tournaments: Vec<TournamentResp>,
+}
+
+#[cw_serde]
+pub struct TournamentMatchResp {
+    pub round: u32,
+    pub index: u32,
+    pub tournament_match: TournamentMatch,
+}
+#[cw_serde]
+pub struct GetTournamentBracketResp {
+    pub matches: Vec<TournamentMatchResp>,
+}
+
+#[cw_serde]
+pub struct SeriesScoreResp {
+    pub best_of: u32,
+    pub contestant1_wins: u32,
+    pub contestant2_wins: u32,
+    pub draws: u32,
+    pub games: Vec<GameRoomStatus>,
+    // the final result once the series is decided
+    pub result: Option<GameRoomStatus>,
+}
+
+#[cw_serde]
+pub struct LeagueResp {
+    pub league_id: u64,
+    pub league: League,
+}
+#[cw_serde]
+pub struct ListLeaguesResp {
+    pub leagues: Vec<LeagueResp>,
+}
+
+#[cw_serde]
+pub struct LeagueStandingEntry {
+    pub position: u32,
+    pub player: String,
+    pub standing: LeagueStanding,
+}
+#[cw_serde]
+pub struct LeagueStandingsResp {
+    pub standings: Vec<LeagueStandingEntry>,
+}
+
+#[cw_serde]
+pub struct LeagueFixtureResp {
+    pub game_room_id: u64,
+    pub division: u32,
+}
+#[cw_serde]
+pub struct LeagueFixturesResp {
+    pub fixtures: Vec<LeagueFixtureResp>,
+}
+
+#[cw_serde]
+pub struct GameRoomResp {
+    pub game_room_id: u64,
+    pub state: GameRoomsState,
+}
+#[cw_serde]
+pub struct ListGameRoomsResp {
+    pub rooms: Vec<GameRoomResp>,
+}
 
 #[cw_serde]
 pub struct Cw20ReceiveMsg {
@@ -0,0 +1,74 @@
+use cosmwasm_std::{attr, Deps, DepsMut, MessageInfo, Response, StdResult, Storage};
+
+use crate::error::ContractError;
+use crate::msg::{GetPauseStateResp, Role};
+use crate::roles::ensure_role;
+use crate::state::{PauseState, PAUSE_STATE};
+
+pub enum Operation {
+    Deposits,
+    Withdrawals,
+    RoomCreation,
+    RoomSettlement,
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Deposits => "deposits",
+            Operation::Withdrawals => "withdrawals",
+            Operation::RoomCreation => "room_creation",
+            Operation::RoomSettlement => "room_settlement",
+        }
+    }
+
+    fn is_paused(&self, state: &PauseState) -> bool {
+        match self {
+            Operation::Deposits => state.deposits,
+            Operation::Withdrawals => state.withdrawals,
+            Operation::RoomCreation => state.room_creation,
+            Operation::RoomSettlement => state.room_settlement,
+        }
+    }
+}
+
+pub fn execute_set_pause_state(
+    deps: DepsMut,
+    info: MessageInfo,
+    pause_state: PauseState,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info, Role::Pauser)?;
+
+    PAUSE_STATE.save(deps.storage, &pause_state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_pause_state"),
+        attr("deposits", pause_state.deposits.to_string()),
+        attr("withdrawals", pause_state.withdrawals.to_string()),
+        attr("room_creation", pause_state.room_creation.to_string()),
+        attr("room_settlement", pause_state.room_settlement.to_string()),
+    ]))
+}
+
+pub fn execute_withdraw_only(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    execute_set_pause_state(deps, info, PauseState::withdraw_only())
+        .map(|res| res.add_attribute("mode", "withdraw_only"))
+}
+
+pub fn ensure_not_paused(storage: &dyn Storage, operation: Operation) -> Result<(), ContractError> {
+    let pause_state = PAUSE_STATE.may_load(storage)?.unwrap_or_default();
+    if operation.is_paused(&pause_state) {
+        return Err(ContractError::OperationPaused {
+            operation: operation.name().to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub fn query_pause_state(deps: Deps) -> StdResult<GetPauseStateResp> {
+    let pause_state = PAUSE_STATE.may_load(deps.storage)?.unwrap_or_default();
+    Ok(GetPauseStateResp {
+        withdraw_only: pause_state == PauseState::withdraw_only(),
+        pause_state,
+    })
+}
@@ -0,0 +1,156 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128,
+};
+
+use crate::contract::execute::{ensure_valid_stake, room_deadline, save_new_game_room};
+use crate::error::ContractError;
+use crate::fees::{credit_fees, load_fee_config};
+use crate::msg::{CreateGameRoomResp, GameRoomStatus, Role};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::roles::ensure_role;
+use crate::state::{game_rooms_state, Balance, FeeConfig, GameRoomsState, BALANCES};
+
+pub const MIN_RANKED_CONTESTANTS: u32 = 3;
+pub const MAX_RANKED_CONTESTANTS: u32 = 10;
+// the payout table shares add up to the whole pool
+const TOTAL_SHARES: u16 = 10_000;
+
+pub fn execute_create_ranked_room(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contestants: Vec<String>,
+    stake: Uint128,
+    payout_table: Vec<u16>,
+) -> Result<Response, ContractError> {
+    // sender must be a game operator
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let count = contestants.len() as u32;
+    let mut distinct = contestants.clone();
+    distinct.sort();
+    distinct.dedup();
+    if !(MIN_RANKED_CONTESTANTS..=MAX_RANKED_CONTESTANTS).contains(&count)
+        || distinct.len() != contestants.len()
+    {
+        return Err(ContractError::InvalidContestants {
+            min: MIN_RANKED_CONTESTANTS,
+            max: MAX_RANKED_CONTESTANTS,
+        });
+    }
+    if payout_table.is_empty()
+        || payout_table.len() > contestants.len()
+        || payout_table.iter().map(|share| *share as u32).sum::<u32>() != TOTAL_SHARES as u32
+    {
+        return Err(ContractError::InvalidPayoutTable {});
+    }
+    ensure_valid_stake(deps.storage, stake)?;
+
+    // like the duels nothing is locked until every contestant joined
+    let game_room = GameRoomsState {
+        contestant1: contestants[0].clone(),
+        contestant2: contestants[1].clone(),
+        prize_pool: stake * Uint128::from(count),
+        status: GameRoomStatus::Pending { joined: vec![] },
+        created_at: env.block.time,
+        created_height: env.block.height,
+        deadline: room_deadline(deps.storage, &env.block)?,
+        secret_hash: None,
+        extra_contestants: contestants[2..].to_vec(),
+        payout_table,
+        series: None,
+        stakes: vec![],
+        payout_policy: Default::default(),
+        teams: vec![],
+        fee_config: Some(load_fee_config(deps.storage, None)?),
+    };
+    let game_room_id = save_new_game_room(deps.storage, &game_room)?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&CreateGameRoomResp(game_room_id))?)
+        .add_attributes(vec![
+            attr("action", "create_ranked_room"),
+            attr("room_id", game_room_id.to_string()),
+            attr("contestants", count.to_string()),
+            attr("stake", stake),
+        ]))
+}
+
+// what each player takes out of the pool in the order of `ranking`. every contestant pays
+// the fee, the rest of the pool is split by the payout table and the rounding dust goes to the winner.
+pub fn ranked_payouts(
+    game_room: &GameRoomsState,
+    ranking: &[String],
+    fee_config: &FeeConfig,
+) -> StdResult<Vec<Uint128>> {
+    let total_fee =
+        fee_config.fee(game_room.stake()) * Uint128::from(game_room.contestants().len() as u128);
+    let pool = game_room.prize_pool.checked_sub(total_fee)?;
+
+    let mut payouts: Vec<Uint128> = ranking
+        .iter()
+        .enumerate()
+        .map(|(rank, _)| {
+            let share = game_room
+                .payout_table
+                .get(rank)
+                .copied()
+                .unwrap_or_default();
+            pool.multiply_ratio(share, TOTAL_SHARES)
+        })
+        .collect();
+    let paid = payouts.iter().sum::<Uint128>();
+    payouts[0] += pool - paid;
+    Ok(payouts)
+}
+
+pub fn settle_ranked_room(
+    storage: &mut dyn Storage,
+    game_room_id: u64,
+    game_room: &GameRoomsState,
+    ranking: Vec<String>,
+    fee_config: &FeeConfig,
+) -> Result<(), ContractError> {
+    let contestants = game_room.contestants();
+    let mut ranked = ranking.clone();
+    ranked.sort();
+    ranked.dedup();
+    if ranked.len() != ranking.len()
+        || ranking.len() != contestants.len()
+        || !ranking.iter().all(|player| contestants.contains(&player))
+    {
+        return Err(ContractError::InvalidRanking {});
+    }
+
+    let stake = game_room.stake();
+    let payouts = ranked_payouts(game_room, &ranking, fee_config)?;
+    for (player, payout) in ranking.iter().zip(payouts) {
+        BALANCES.update(
+            storage,
+            &Addr::unchecked(player),
+            |balance: Option<Balance>| -> StdResult<_> {
+                Ok(balance
+                    .unwrap_or_default()
+                    .unlock_and_decrease(stake, stake)
+                    .total_increase(payout))
+            },
+        )?;
+    }
+
+    let fee = fee_config.fee(stake);
+    credit_fees(
+        storage,
+        &ranking
+            .iter()
+            .map(|player| (player.as_str(), fee))
+            .collect::<Vec<_>>(),
+    )?;
+
+    game_rooms_state().save(
+        storage,
+        game_room_id,
+        &game_room.get_finish_state(GameRoomStatus::Ranked { ranking }),
+    )?;
+    Ok(())
+}
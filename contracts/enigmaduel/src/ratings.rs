@@ -0,0 +1,162 @@
+use cosmwasm_std::{
+    attr, BlockInfo, Decimal, Deps, DepsMut, MessageInfo, Order, Response, StdResult, Storage,
+};
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::msg::{
+    GameRoomStatus, GetEloKFactorResp, GetRatingResp, ListRatingHistoryResp, RatingChangeResp,
+};
+use crate::roles::ensure_admin;
+use crate::scoring::scored_winner;
+use crate::state::{ratings, GameRoomsState, RatingChange, ELO_K_FACTOR, RATING_HISTORY};
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub const DEFAULT_RATING: u32 = 1200;
+pub const DEFAULT_K_FACTOR: u32 = 32;
+// a bigger gap doesn't make the favourite any more likely to win.
+const MAX_RATING_GAP: u32 = 400;
+// 10^(1/400), the odds of the favourite grow by this ratio with each rating point.
+const POINT_ODDS: Decimal = Decimal::raw(1_005_773_063_001_738_243);
+
+// the chance of winning against the opponent, a draw counts as half a win.
+pub fn expected_score(rating: u32, opponent_rating: u32) -> Decimal {
+    let odds = POINT_ODDS.pow(rating.abs_diff(opponent_rating).min(MAX_RATING_GAP));
+    let favourite = odds / (Decimal::one() + odds);
+    if rating >= opponent_rating {
+        favourite
+    } else {
+        Decimal::one() - favourite
+    }
+}
+
+// the new ratings of both sides, what one side gains the other loses.
+pub fn rate_duel(rating1: u32, rating2: u32, score1: Decimal, k_factor: u32) -> (u32, u32) {
+    let expected1 = expected_score(rating1, rating2);
+    let k_factor = Decimal::from_ratio(k_factor, 1u32);
+    let half = Decimal::percent(50);
+
+    if score1 >= expected1 {
+        let points = (k_factor * (score1 - expected1) + half)
+            .to_uint_floor()
+            .u128() as u32;
+        (rating1 + points, rating2.saturating_sub(points))
+    } else {
+        let points = (k_factor * (expected1 - score1) + half)
+            .to_uint_floor()
+            .u128() as u32;
+        (rating1.saturating_sub(points), rating2 + points)
+    }
+}
+
+// rates both contestants of the settled room, the results that don't settle a room are ignored.
+pub fn update_ratings(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    game_room_id: u64,
+    room: &GameRoomsState,
+    result: &GameRoomStatus,
+) -> StdResult<()> {
+    let score1 = match result {
+        GameRoomStatus::Win { addr } if *addr == room.contestant1 => Decimal::one(),
+        GameRoomStatus::Win { .. } => Decimal::zero(),
+        GameRoomStatus::Draw {} => Decimal::percent(50),
+        GameRoomStatus::Scored { scores } => match scored_winner(room, scores) {
+            Some(winner) if winner == room.contestant1 => Decimal::one(),
+            Some(_) => Decimal::zero(),
+            None => Decimal::percent(50),
+        },
+        _ => return Ok(()),
+    };
+    let k_factor = ELO_K_FACTOR.may_load(storage)?.unwrap_or(DEFAULT_K_FACTOR);
+
+    let rating1 = load_rating(storage, &room.contestant1)?;
+    let rating2 = load_rating(storage, &room.contestant2)?;
+    let (new_rating1, new_rating2) = rate_duel(rating1, rating2, score1, k_factor);
+
+    let changes = [
+        (&room.contestant1, &room.contestant2, rating1, new_rating1),
+        (&room.contestant2, &room.contestant1, rating2, new_rating2),
+    ];
+    for (player, opponent, rating_before, rating_after) in changes {
+        ratings().save(storage, player, &rating_after)?;
+        RATING_HISTORY.save(
+            storage,
+            (player, game_room_id),
+            &RatingChange {
+                opponent: opponent.clone(),
+                rating_before,
+                rating_after,
+                finished_at: block.time,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+pub fn load_rating(storage: &dyn Storage, player: &str) -> StdResult<u32> {
+    Ok(ratings()
+        .may_load(storage, player)?
+        .unwrap_or(DEFAULT_RATING))
+}
+
+pub fn execute_update_k_factor(
+    deps: DepsMut,
+    info: MessageInfo,
+    k_factor: u32,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    if k_factor == 0 {
+        return Err(ContractError::InvalidKFactor {});
+    }
+
+    // applies to the rooms settled from now on
+    ELO_K_FACTOR.save(deps.storage, &k_factor)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_elo_k_factor"),
+        attr("k_factor", k_factor.to_string()),
+    ]))
+}
+
+pub fn query_rating(deps: Deps, player: String) -> StdResult<GetRatingResp> {
+    Ok(GetRatingResp {
+        rating: load_rating(deps.storage, &player)?,
+    })
+}
+
+pub fn query_k_factor(deps: Deps) -> StdResult<GetEloKFactorResp> {
+    Ok(GetEloKFactorResp {
+        k_factor: ELO_K_FACTOR
+            .may_load(deps.storage)?
+            .unwrap_or(DEFAULT_K_FACTOR),
+    })
+}
+
+// the rating changes of the player, the most recent first.
+pub fn query_rating_history(
+    deps: Deps,
+    player: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListRatingHistoryResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_after.map(Bound::exclusive);
+
+    let changes = RATING_HISTORY
+        .prefix(&player)
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(game_room_id, change)| RatingChangeResp {
+                game_room_id,
+                change,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListRatingHistoryResp { changes })
+}
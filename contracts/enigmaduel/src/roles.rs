@@ -0,0 +1,110 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, Empty, MessageInfo, Order, Response, StdResult, Storage,
+};
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::msg::{GetUserRolesResp, ListRoleMembersResp, Role};
+use crate::state::{ADMIN, ROLES};
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn execute_grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    addr: String,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+
+    let addr = deps.api.addr_validate(&addr)?;
+    ROLES.save(deps.storage, (role.key(), &addr), &Empty {})?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "grant_role"),
+        attr("role", role.key()),
+        attr("addr", addr),
+    ]))
+}
+
+pub fn execute_revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    addr: String,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+
+    let addr = deps.api.addr_validate(&addr)?;
+    ROLES.remove(deps.storage, (role.key(), &addr));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_role"),
+        attr("role", role.key()),
+        attr("addr", addr),
+    ]))
+}
+
+pub fn has_role(storage: &dyn Storage, role: &Role, addr: &Addr) -> bool {
+    ROLES.has(storage, (role.key(), addr))
+}
+
+/// hands every role of the outgoing admin to the incoming one, or drops them when the admin is renounced.
+pub fn transfer_roles(storage: &mut dyn Storage, from: &Addr, to: Option<&Addr>) -> StdResult<()> {
+    for role in Role::all() {
+        if has_role(storage, &role, from) {
+            ROLES.remove(storage, (role.key(), from));
+            if let Some(to) = to {
+                ROLES.save(storage, (role.key(), to), &Empty {})?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// the admin is the config owner, it manages the roles and the admin rotation.
+/// fails for everyone once the admin is renounced.
+pub fn ensure_admin(deps: Deps, info: &MessageInfo) -> Result<Addr, ContractError> {
+    match ADMIN.may_load(deps.storage)? {
+        Some(admin) if admin == info.sender => Ok(admin),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub fn ensure_role(deps: Deps, info: &MessageInfo, role: Role) -> Result<(), ContractError> {
+    if !has_role(deps.storage, &role, &info.sender) {
+        return Err(ContractError::MissingRole { role });
+    }
+    Ok(())
+}
+
+pub fn query_role_members(
+    deps: Deps,
+    role: Role,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListRoleMembersResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+
+    let members = ROLES
+        .prefix(role.key())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(Into::into))
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListRoleMembersResp { members })
+}
+
+pub fn query_user_roles(deps: Deps, user: String) -> StdResult<GetUserRolesResp> {
+    let user = Addr::unchecked(user);
+    let roles = Role::all()
+        .into_iter()
+        .filter(|role| has_role(deps.storage, role, &user))
+        .collect();
+
+    Ok(GetUserRolesResp { roles })
+}
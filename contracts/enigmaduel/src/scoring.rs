@@ -0,0 +1,153 @@
+use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
+
+use crate::error::ContractError;
+use crate::fees::credit_fees;
+use crate::msg::{GameRoomStatus, PayoutPolicy};
+use crate::state::{game_rooms_state, Balance, FeeConfig, GameRoomsState, BALANCES};
+
+// the refund of the margin policy is in basis points of the stake
+const TOTAL_SHARES: u16 = 10_000;
+
+pub fn ensure_valid_payout_policy(policy: &PayoutPolicy) -> Result<(), ContractError> {
+    match policy {
+        PayoutPolicy::MarginRefund { refund, .. } if *refund > TOTAL_SHARES => {
+            Err(ContractError::InvalidPayoutPolicy {})
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn ensure_valid_scores(
+    game_room: &GameRoomsState,
+    scores: &[(String, u32)],
+) -> Result<(), ContractError> {
+    let contestants = game_room.contestants();
+    if scores.len() != contestants.len()
+        || !contestants.iter().all(|contestant| {
+            scores
+                .iter()
+                .filter(|(player, _)| player == *contestant)
+                .count()
+                == 1
+        })
+    {
+        return Err(ContractError::InvalidScores {});
+    }
+    Ok(())
+}
+
+// the contestants scores in the order of `contestants`
+fn contestant_scores(game_room: &GameRoomsState, scores: &[(String, u32)]) -> Vec<u32> {
+    game_room
+        .contestants()
+        .into_iter()
+        .map(|contestant| {
+            scores
+                .iter()
+                .find(|(player, _)| player == contestant)
+                .map(|(_, score)| *score)
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+// the contestant with the best score, nobody when the scores are equal
+pub fn scored_winner(game_room: &GameRoomsState, scores: &[(String, u32)]) -> Option<String> {
+    let points = contestant_scores(game_room, scores);
+    match points[0].cmp(&points[1]) {
+        std::cmp::Ordering::Greater => Some(game_room.contestant1.clone()),
+        std::cmp::Ordering::Less => Some(game_room.contestant2.clone()),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+// what each contestant takes back out of the pool and pays in fees, in the order of `contestants`.
+// equal scores refund the stakes minus the draw fee like a draw, otherwise both contestants pay
+// the fee and the rest is split with the payout policy, the rounding dust goes to the winner.
+pub fn scored_payouts(
+    game_room: &GameRoomsState,
+    scores: &[(String, u32)],
+    fee_config: &FeeConfig,
+) -> StdResult<(Vec<Uint128>, Vec<Uint128>)> {
+    let stakes = game_room.stakes();
+    let points = contestant_scores(game_room, scores);
+    let (winner, loser) = match points[0].cmp(&points[1]) {
+        std::cmp::Ordering::Greater => (0, 1),
+        std::cmp::Ordering::Less => (1, 0),
+        std::cmp::Ordering::Equal => {
+            let draw_fees: Vec<Uint128> = stakes
+                .iter()
+                .map(|stake| fee_config.draw_fee(*stake))
+                .collect();
+            let payouts = stakes
+                .iter()
+                .zip(&draw_fees)
+                .map(|(stake, draw_fee)| stake.checked_sub(*draw_fee))
+                .collect::<Result<_, _>>()?;
+            return Ok((payouts, draw_fees));
+        }
+    };
+
+    let fees: Vec<Uint128> = stakes.iter().map(|stake| fee_config.fee(*stake)).collect();
+    let pool = game_room.prize_pool.checked_sub(fees.iter().sum())?;
+    let mut payouts = vec![Uint128::zero(); 2];
+    match game_room.payout_policy {
+        PayoutPolicy::WinnerTakesAll {} => {}
+        PayoutPolicy::Proportional {} => {
+            let total = points[0] as u128 + points[1] as u128;
+            payouts[loser] = pool.multiply_ratio(points[loser] as u128, total);
+        }
+        PayoutPolicy::MarginRefund { margin, refund } => {
+            if points[winner] - points[loser] <= margin {
+                payouts[loser] = stakes[loser].multiply_ratio(refund, TOTAL_SHARES).min(pool);
+            }
+        }
+    }
+    payouts[winner] = pool - payouts[loser];
+
+    Ok((payouts, fees))
+}
+
+pub fn settle_scored_room(
+    storage: &mut dyn Storage,
+    game_room_id: u64,
+    game_room: &GameRoomsState,
+    scores: Vec<(String, u32)>,
+    fee_config: &FeeConfig,
+) -> Result<(), ContractError> {
+    let (payouts, fees) = scored_payouts(game_room, &scores, fee_config)?;
+
+    for ((contestant, stake), payout) in game_room
+        .contestants()
+        .into_iter()
+        .zip(game_room.stakes())
+        .zip(payouts)
+    {
+        BALANCES.update(
+            storage,
+            &Addr::unchecked(contestant),
+            |balance: Option<Balance>| -> StdResult<_> {
+                Ok(balance
+                    .unwrap_or_default()
+                    .unlock_and_decrease(stake, stake)
+                    .total_increase(payout))
+            },
+        )?;
+    }
+    credit_fees(
+        storage,
+        &game_room
+            .contestants()
+            .into_iter()
+            .map(String::as_str)
+            .zip(fees)
+            .collect::<Vec<_>>(),
+    )?;
+
+    game_rooms_state().save(
+        storage,
+        game_room_id,
+        &game_room.get_finish_state(GameRoomStatus::Scored { scores }),
+    )?;
+    Ok(())
+}
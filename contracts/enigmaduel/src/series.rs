@@ -0,0 +1,138 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+};
+
+use crate::contract::execute::{
+    ensure_valid_stake, room_deadline, save_new_game_room, settle_game_room,
+};
+use crate::error::ContractError;
+use crate::fees::load_fee_config;
+use crate::msg::{CreateGameRoomResp, GameRoomStatus, Role, SeriesScoreResp};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::roles::ensure_role;
+use crate::state::{game_rooms_state, GameRoomsState, SeriesScore};
+
+pub const MAX_BEST_OF: u32 = 9;
+
+pub fn execute_create_series_room(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contestant1: String,
+    contestant2: String,
+    stake: Uint128,
+    best_of: u32,
+) -> Result<Response, ContractError> {
+    // sender must be a game operator
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    if !(3..=MAX_BEST_OF).contains(&best_of) || best_of.is_multiple_of(2) {
+        return Err(ContractError::InvalidBestOf { max: MAX_BEST_OF });
+    }
+    ensure_valid_stake(deps.storage, stake)?;
+
+    // like the single games nothing is locked until both of the contestants joined
+    let game_room = GameRoomsState {
+        contestant1,
+        contestant2,
+        prize_pool: stake + stake,
+        status: GameRoomStatus::Pending { joined: vec![] },
+        created_at: env.block.time,
+        created_height: env.block.height,
+        deadline: room_deadline(deps.storage, &env.block)?,
+        secret_hash: None,
+        extra_contestants: vec![],
+        payout_table: vec![],
+        stakes: vec![],
+        payout_policy: Default::default(),
+        teams: vec![],
+        fee_config: Some(load_fee_config(deps.storage, None)?),
+        series: Some(SeriesScore {
+            best_of,
+            games: vec![],
+        }),
+    };
+    let game_room_id = save_new_game_room(deps.storage, &game_room)?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&CreateGameRoomResp(game_room_id))?)
+        .add_attributes(vec![
+            attr("action", "create_series_room"),
+            attr("room_id", game_room_id.to_string()),
+            attr("best_of", best_of.to_string()),
+            attr("stake", stake),
+        ]))
+}
+
+pub fn execute_report_series_game(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    game_room_id: u64,
+    result: GameRoomStatus,
+) -> Result<Response, ContractError> {
+    // sender must be a game operator
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomSettlement)?;
+
+    let mut game_room = game_rooms_state().load(deps.storage, game_room_id)?;
+    if game_room.status != (GameRoomStatus::Started {}) {
+        return Err(ContractError::GameRoomNotStarted {});
+    }
+    let Some(mut series) = game_room.series.clone() else {
+        return Err(ContractError::NotASeries {});
+    };
+    match &result {
+        GameRoomStatus::Win { addr } if game_room.contestants().contains(&addr) => {}
+        GameRoomStatus::Win { .. } => return Err(ContractError::NotAContestant {}),
+        GameRoomStatus::Draw {} => {}
+        _ => return Err(ContractError::InvalidRoomResult {}),
+    }
+
+    series.games.push(result);
+    let game = series.games.len();
+    let series_result = series.result(&game_room.contestant1, &game_room.contestant2);
+    game_room.series = Some(series);
+
+    // the stakes only move once the series is decided,
+    // until then every reported game gives the operators another timeout for the next one
+    let Some(series_result) = series_result else {
+        game_room.deadline = room_deadline(deps.storage, &env.block)?;
+        game_rooms_state().save(deps.storage, game_room_id, &game_room)?;
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "report_series_game"),
+            attr("room_id", game_room_id.to_string()),
+            attr("game", game.to_string()),
+        ]));
+    };
+    let response = settle_game_room(
+        deps.storage,
+        &env.block,
+        game_room_id,
+        game_room,
+        series_result,
+    )?;
+
+    Ok(response.add_attributes(vec![
+        attr("room_id", game_room_id.to_string()),
+        attr("game", game.to_string()),
+    ]))
+}
+
+pub fn query_series_score(deps: Deps, game_room_id: u64) -> StdResult<SeriesScoreResp> {
+    let game_room = game_rooms_state().load(deps.storage, game_room_id)?;
+    let series = game_room
+        .series
+        .clone()
+        .ok_or_else(|| StdError::not_found("SeriesScore"))?;
+
+    Ok(SeriesScoreResp {
+        best_of: series.best_of,
+        contestant1_wins: series.wins(&game_room.contestant1),
+        contestant2_wins: series.wins(&game_room.contestant2),
+        draws: series.draws(),
+        result: series.result(&game_room.contestant1, &game_room.contestant2),
+        games: series.games,
+    })
+}
@@ -1,9 +1,10 @@
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::{Addr, Uint128, Uint256};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, Empty, Int128, Timestamp, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
-use crate::msg::GameRoomStatus;
+use crate::helpers::pair_key;
+use crate::msg::{GameRoomStatus, MatchOutcome, PayoutPolicy};
 
 #[cw_serde]
 pub struct GameRoomsState {
@@ -11,19 +12,234 @@ pub struct GameRoomsState {
     pub contestant2: String,
     pub prize_pool: Uint128,
     pub status: GameRoomStatus,
+    pub created_at: Timestamp,
+    pub created_height: u64,
+    // once passed, anyone can expire the room and refund the locked stakes.
+    pub deadline: Timestamp,
+    // sha256 of the invite secret, only the holders of the secret can join a private room.
+    pub secret_hash: Option<Binary>,
+    // the third and later players of a multi-player room, empty for the duels.
+    #[serde(default)]
+    pub extra_contestants: Vec<String>,
+    // the shares of the pool by rank in basis points, only the multi-player rooms have one.
+    #[serde(default)]
+    pub payout_table: Vec<u16>,
+    // the games played so far, only the best-of-n rooms have one.
+    #[serde(default)]
+    pub series: Option<SeriesScore>,
+    // the lock amount of each contestant in the order of `contestants`,
+    // empty when they all put the same share into the prize pool.
+    #[serde(default)]
+    pub stakes: Vec<Uint128>,
+    // how a scored result splits the pool.
+    #[serde(default)]
+    pub payout_policy: PayoutPolicy,
+    // the team of each contestant in the order of `contestants`, only the team rooms have one.
+    #[serde(default)]
+    pub teams: Vec<u8>,
+    // the fee schedule the room settles with, taken when the room was created.
+    // the rooms created before the rooms kept one settle with the contract wide schedule.
+    #[serde(default)]
+    pub fee_config: Option<FeeConfig>,
 }
 
 impl GameRoomsState {
+    pub fn contestants(&self) -> Vec<&String> {
+        let mut contestants = vec![&self.contestant1, &self.contestant2];
+        contestants.extend(&self.extra_contestants);
+        contestants
+    }
+
+    // every contestant puts the same share into the prize pool
+    pub fn stake(&self) -> Uint128 {
+        self.prize_pool
+            .checked_div(Uint128::from(self.contestants().len() as u128))
+            .unwrap()
+    }
+
+    pub fn stakes(&self) -> Vec<Uint128> {
+        if self.stakes.is_empty() {
+            vec![self.stake(); self.contestants().len()]
+        } else {
+            self.stakes.clone()
+        }
+    }
+
+    pub fn is_handicapped(&self) -> bool {
+        !self.stakes.is_empty()
+    }
+
+    pub fn is_team(&self) -> bool {
+        !self.teams.is_empty()
+    }
+
+    pub fn is_ranked(&self) -> bool {
+        !self.payout_table.is_empty()
+    }
+
     pub fn get_finish_state(&self, status: GameRoomStatus) -> Self {
         Self {
-            contestant1: self.contestant1.clone(),
-            contestant2: self.contestant2.clone(),
             prize_pool: Default::default(),
             status,
+            ..self.clone()
         }
     }
 }
+// the stakes are locked once for the whole series, every game is a win or a draw.
 #[cw_serde]
+pub struct SeriesScore {
+    pub best_of: u32,
+    pub games: Vec<GameRoomStatus>,
+}
+
+impl SeriesScore {
+    pub fn required_wins(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    pub fn wins(&self, player: &str) -> u32 {
+        self.games
+            .iter()
+            .filter(|game| matches!(game, GameRoomStatus::Win { addr } if addr == player))
+            .count() as u32
+    }
+
+    pub fn draws(&self) -> u32 {
+        self.games
+            .iter()
+            .filter(|game| **game == GameRoomStatus::Draw {})
+            .count() as u32
+    }
+
+    // the result of the whole series once a player reached the required wins.
+    // once all the games are played the player with more wins takes the series,
+    // it ends drawn only when both won as many games.
+    pub fn result(&self, contestant1: &str, contestant2: &str) -> Option<GameRoomStatus> {
+        let (wins1, wins2) = (self.wins(contestant1), self.wins(contestant2));
+        if wins1.max(wins2) < self.required_wins() && (self.games.len() as u32) < self.best_of {
+            return None;
+        }
+        let winner = match wins1.cmp(&wins2) {
+            std::cmp::Ordering::Greater => contestant1,
+            std::cmp::Ordering::Less => contestant2,
+            std::cmp::Ordering::Equal => return Some(GameRoomStatus::Draw {}),
+        };
+        Some(GameRoomStatus::Win {
+            addr: winner.to_string(),
+        })
+    }
+}
+
+// the fee every contestant of a decided room pays, worked out from their own stake.
+#[cw_serde]
+pub struct FeeConfig {
+    pub flat: Uint128,
+    // on top of the flat fee, in basis points of the stake
+    pub bps: u16,
+    pub min: Option<Uint128>,
+    pub max: Option<Uint128>,
+    pub draw_policy: DrawFeePolicy,
+    // replace the flat and the basis points for the larger stakes, ascending by `min_stake`
+    pub tiers: Vec<FeeTier>,
+}
+
+#[cw_serde]
+pub struct FeeTier {
+    pub min_stake: Uint128,
+    pub flat: Uint128,
+    pub bps: u16,
+}
+
+#[cw_serde]
+pub enum DrawFeePolicy {
+    Free {},
+    Charged {},
+}
+
+impl FeeConfig {
+    pub fn flat(fee: Uint128) -> Self {
+        Self {
+            flat: fee,
+            bps: 0,
+            min: None,
+            max: None,
+            draw_policy: DrawFeePolicy::Free {},
+            tiers: vec![],
+        }
+    }
+
+    pub fn fee(&self, stake: Uint128) -> Uint128 {
+        let (flat, bps) = self
+            .tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.min_stake <= stake)
+            .map_or((self.flat, self.bps), |tier| (tier.flat, tier.bps));
+        let mut fee = flat + stake.multiply_ratio(bps, 10_000u128);
+        if let Some(min) = self.min {
+            fee = fee.max(min);
+        }
+        if let Some(max) = self.max {
+            fee = fee.min(max);
+        }
+        fee
+    }
+
+    pub fn draw_fee(&self, stake: Uint128) -> Uint128 {
+        match self.draw_policy {
+            DrawFeePolicy::Free {} => Uint128::zero(),
+            DrawFeePolicy::Charged {} => self.fee(stake),
+        }
+    }
+}
+
+// where a share of the fees goes
+#[cw_serde]
+pub enum FeeDestination {
+    // credited to the internal balance of the address, e.g. the treasury or the jackpot
+    Balance { addr: String },
+    // burnt from the supply of the duel token
+    Burn {},
+    // credited to whoever referred the contestant that paid the fee
+    Referrer {},
+}
+
+impl FeeDestination {
+    // the key of what the destination was credited, a renamed recipient keeps it
+    pub fn key(&self) -> &str {
+        match self {
+            FeeDestination::Balance { addr } => addr,
+            FeeDestination::Burn {} => "burn",
+            FeeDestination::Referrer {} => "referrer",
+        }
+    }
+}
+
+#[cw_serde]
+pub struct FeeRecipient {
+    pub name: String,
+    pub destination: FeeDestination,
+    pub weight: u32,
+}
+
+// the fees kept by the contract, apart from every player balance
+#[cw_serde]
+#[derive(Default)]
+pub struct FeeLedger {
+    // everything credited so far
+    pub accrued: Uint128,
+    // everything the treasurers took out
+    pub collected: Uint128,
+}
+
+impl FeeLedger {
+    pub fn available(&self) -> Uint128 {
+        self.accrued - self.collected
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
 pub struct Balance {
     pub total: Uint128,
     pub locked: Uint128,
@@ -58,22 +274,6 @@ impl Balance {
         }
     }
 
-    pub fn unlock_and_increase(
-        &mut self,
-        unlock_amount: Uint128,
-        increase_amount: Uint128,
-    ) -> Self {
-        Self {
-            total: self
-                .total
-                .checked_add(unlock_amount)
-                .unwrap()
-                .checked_add(increase_amount + unlock_amount)
-                .unwrap(),
-            locked: self.locked.checked_sub(unlock_amount).unwrap(),
-        }
-    }
-
     pub fn unlock_and_decrease(
         &mut self,
         unlock_amount: Uint128,
@@ -98,18 +298,338 @@ impl Balance {
     }
 }
 
-impl Default for Balance {
-    fn default() -> Self {
+// the operations halted by the circuit breaker, everything runs when missing.
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseState {
+    pub deposits: bool,
+    pub withdrawals: bool,
+    pub room_creation: bool,
+    pub room_settlement: bool,
+}
+
+impl PauseState {
+    // everything is halted except withdrawing the unlocked funds.
+    pub fn withdraw_only() -> Self {
         Self {
-            total: Default::default(),
-            locked: Default::default(),
+            deposits: true,
+            withdrawals: false,
+            room_creation: true,
+            room_settlement: true,
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PlayerStats {
+    pub games_played: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub draws: u64,
+    // the sum of the player stakes in the settled rooms
+    pub total_wagered: Uint128,
+    // the winnings minus the lost stakes
+    pub net_profit: Int128,
+    pub fees_paid: Uint128,
+}
+
+// a settled room from the point of view of one of its contestants.
+#[cw_serde]
+pub struct MatchRecord {
+    // the single opponent of the player, empty when there were several.
+    pub opponent: String,
+    // the other contestants of a multi-player room, or the other side of a team room.
+    #[serde(default)]
+    pub opponents: Vec<String>,
+    pub stake: Uint128,
+    pub outcome: MatchOutcome,
+    pub net_profit: Int128,
+    pub fee: Uint128,
+    pub finished_at: Timestamp,
+}
+
+// a rating update from a settled room.
+#[cw_serde]
+pub struct RatingChange {
+    pub opponent: String,
+    pub rating_before: u32,
+    pub rating_after: u32,
+    pub finished_at: Timestamp,
+}
+
+// a player waiting in the matchmaking queue.
+#[cw_serde]
+pub struct QueueEntry {
+    pub player: String,
+    pub rating: u32,
+    // the widest rating gap the player accepts, any opponent when missing
+    pub max_rating_gap: Option<u32>,
+    pub joined_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct QueueTicket {
+    pub stake: Uint128,
+    pub ticket: u64,
+}
+
+// a stake waiting for a specific opponent or for anyone to accept it.
+#[cw_serde]
+pub struct Challenge {
+    pub challenger: String,
+    pub opponent: Option<String>,
+    pub stake: Uint128,
+    pub expires: Timestamp,
+    pub created_at: Timestamp,
+}
+
+#[cw_serde]
+pub enum TournamentStatus {
+    Registration {},
+    Running {},
+    Finished { champion: String },
+    Cancelled {},
+}
+
+#[cw_serde]
+pub struct Tournament {
+    pub name: String,
+    pub entry_fee: Uint128,
+    pub max_players: u32,
+    // the shares of the pool in basis points, the champion first and then
+    // the players knocked out in each earlier round.
+    pub payout_table: Vec<u16>,
+    pub players: Vec<String>,
+    // known once the bracket is drawn
+    pub rounds: u32,
+    pub status: TournamentStatus,
+    // the fee schedule the tournament pays out with, taken when it was created.
+    // the tournaments created before they kept one pay out with the contract wide schedule.
+    #[serde(default)]
+    pub fee_config: Option<FeeConfig>,
+    // anyone can cancel the tournament once it passes, pushed back whenever a player registers,
+    // the bracket is drawn or a match is settled. the older tournaments get one on their next move.
+    #[serde(default)]
+    pub deadline: Option<Timestamp>,
+}
+
+// a match of the bracket, a match without the second player is a bye.
+#[cw_serde]
+pub struct TournamentMatch {
+    pub player1: String,
+    pub player2: Option<String>,
+    pub game_room_id: Option<u64>,
+    pub winner: Option<String>,
+}
+
+impl TournamentMatch {
+    pub fn loser(&self) -> Option<String> {
+        let player2 = self.player2.clone()?;
+        match self.winner.as_ref()? {
+            winner if *winner == player2 => Some(self.player1.clone()),
+            _ => Some(player2),
         }
     }
 }
 
+#[cw_serde]
+pub struct TournamentMatchRef {
+    pub tournament_id: u64,
+    pub round: u32,
+    pub index: u32,
+}
+
+#[cw_serde]
+pub enum LeagueStatus {
+    Registration {},
+    Running {},
+    Cancelled {},
+}
+
+#[cw_serde]
+pub struct League {
+    pub name: String,
+    pub entry_fee: Uint128,
+    pub divisions: u32,
+    pub division_size: u32,
+    // the players promoted from and relegated to each division when a season ends
+    pub movers: u32,
+    // the shares of each division pool in basis points, by the final position
+    pub payout_table: Vec<u16>,
+    pub season: u32,
+    // the rooms of the running season still waiting for a result
+    pub open_fixtures: u32,
+    pub status: LeagueStatus,
+    // the fee schedule the seasons pay out with, taken when the league was created.
+    // the leagues created before the leagues kept one pay out with the contract wide schedule.
+    #[serde(default)]
+    pub fee_config: Option<FeeConfig>,
+    // anyone can cancel the league once it passes, pushed back whenever a player registers,
+    // a season starts or ends or a fixture is settled. the older leagues get one on their next move.
+    #[serde(default)]
+    pub deadline: Option<Timestamp>,
+}
+
+// a row of the points table, three points for a win and one for a draw.
+#[cw_serde]
+#[derive(Default)]
+pub struct LeagueStanding {
+    pub division: u32,
+    pub points: u32,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+#[cw_serde]
+pub struct LeagueFixture {
+    pub league_id: u64,
+    pub season: u32,
+    pub division: u32,
+}
+
+pub struct GameRoomIndexes<'a> {
+    pub pair: MultiIndex<'a, (String, String), GameRoomsState, u64>,
+    pub status: MultiIndex<'a, String, GameRoomsState, u64>,
+}
+
+impl<'a> IndexList<GameRoomsState> for GameRoomIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<GameRoomsState>> + '_> {
+        let v: Vec<&dyn Index<GameRoomsState>> = vec![&self.pair, &self.status];
+        Box::new(v.into_iter())
+    }
+}
+
+// room id => room, indexed by the contestants pair and the status kind
+pub fn game_rooms_state<'a>() -> IndexedMap<'a, u64, GameRoomsState, GameRoomIndexes<'a>> {
+    let indexes = GameRoomIndexes {
+        pair: MultiIndex::new(
+            |_pk, room| pair_key(&room.contestant1, &room.contestant2),
+            "rooms",
+            "rooms__pair",
+        ),
+        status: MultiIndex::new(
+            |_pk, room| room.status.kind().key().to_string(),
+            "rooms",
+            "rooms__status",
+        ),
+    };
+    IndexedMap::new("rooms", indexes)
+}
+
+pub struct PlayerStatsIndexes<'a> {
+    pub wins: MultiIndex<'a, u64, PlayerStats, String>,
+    pub net_profit: MultiIndex<'a, i128, PlayerStats, String>,
+    pub total_wagered: MultiIndex<'a, u128, PlayerStats, String>,
+}
+
+impl<'a> IndexList<PlayerStats> for PlayerStatsIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<PlayerStats>> + '_> {
+        let v: Vec<&dyn Index<PlayerStats>> =
+            vec![&self.wins, &self.net_profit, &self.total_wagered];
+        Box::new(v.into_iter())
+    }
+}
+
+// player => stats, indexed by the leaderboard values
+pub fn player_stats<'a>() -> IndexedMap<'a, &'a str, PlayerStats, PlayerStatsIndexes<'a>> {
+    let indexes = PlayerStatsIndexes {
+        wins: MultiIndex::new(
+            |_pk, stats| stats.wins,
+            "player_stats",
+            "player_stats__wins",
+        ),
+        net_profit: MultiIndex::new(
+            |_pk, stats| stats.net_profit.i128(),
+            "player_stats",
+            "player_stats__net_profit",
+        ),
+        total_wagered: MultiIndex::new(
+            |_pk, stats| stats.total_wagered.u128(),
+            "player_stats",
+            "player_stats__total_wagered",
+        ),
+    };
+    IndexedMap::new("player_stats", indexes)
+}
+
+pub struct RatingIndexes<'a> {
+    pub rating: MultiIndex<'a, u32, u32, String>,
+}
+
+impl<'a> IndexList<u32> for RatingIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<u32>> + '_> {
+        let v: Vec<&dyn Index<u32>> = vec![&self.rating];
+        Box::new(v.into_iter())
+    }
+}
+
+// player => elo rating, the unrated players start from the default rating.
+pub fn ratings<'a>() -> IndexedMap<'a, &'a str, u32, RatingIndexes<'a>> {
+    let indexes = RatingIndexes {
+        rating: MultiIndex::new(|_pk, rating| *rating, "ratings", "ratings__rating"),
+    };
+    IndexedMap::new("ratings", indexes)
+}
+
 pub const ADMIN: Item<Addr> = Item::new("admin");
-pub const FEE: Item<Uint128> = Item::new("fee");
+pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+// (role key, member) => membership
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+pub const FEE_CONFIG: Item<FeeConfig> = Item::new("fee_config");
+pub const FEE_LEDGER: Item<FeeLedger> = Item::new("fee_ledger");
+// the fees are split between these by weight, the ledger takes all of them while it's empty
+pub const FEE_RECIPIENTS: Item<Vec<FeeRecipient>> = Item::new("fee_recipients");
+// destination key => what it was credited so far
+pub const FEE_ACCRUED: Map<&str, Uint128> = Map::new("fee_accrued_by_destination");
+// the burn shares waiting for the next `BurnFees`
+pub const PENDING_BURN: Item<Uint128> = Item::new("pending_burn");
+// player => the player that referred them
+pub const REFERRERS: Map<&Addr, Addr> = Map::new("referrers");
+// seconds a room can stay unsettled before it can be expired.
+pub const ROOM_TIMEOUT: Item<u64> = Item::new("room_timeout");
 pub const BALANCES: Map<&Addr, Balance> = Map::new("balance");
-pub const GAME_ROOMS_STATE: Map<String, GameRoomsState> = Map::new("game_rooms");
-pub const GAME_ROOMS_COUNT: Item<Uint256> = Item::new("game_room_count");
+// the id of the last created room, room ids start from one.
+pub const GAME_ROOMS_COUNT: Item<u64> = Item::new("game_room_count");
+// (player, room id) for every contestant of a room, team members and the extra players of a ranked room included
+pub const PLAYER_ROOMS: Map<(&str, u64), Empty> = Map::new("player_rooms");
 pub const ENIGMA_DUEL_TOKEN: Item<Addr> = Item::new("enigma_duel_token");
+pub const PAUSE_STATE: Item<PauseState> = Item::new("pause_state");
+// secp256k1 public keys the players sign their room consents with.
+pub const CONSENT_KEYS: Map<&Addr, Binary> = Map::new("consent_keys");
+// (player, room id) => the settled room
+pub const PLAYER_HISTORY: Map<(&str, u64), MatchRecord> = Map::new("player_history");
+// (player, room id) => the rating update
+pub const RATING_HISTORY: Map<(&str, u64), RatingChange> = Map::new("rating_history");
+pub const ELO_K_FACTOR: Item<u32> = Item::new("elo_k_factor");
+// (stake, ticket) => waiting player, the tickets grow so the oldest player comes first
+pub const QUEUE: Map<(u128, u64), QueueEntry> = Map::new("queue");
+// player => the queue position, a player waits in a single tier at a time
+pub const QUEUED_PLAYERS: Map<&str, QueueTicket> = Map::new("queued_players");
+pub const QUEUE_TICKETS: Item<u64> = Item::new("queue_tickets");
+// challenge id => open challenge, removed once accepted or cancelled
+pub const CHALLENGES: Map<u64, Challenge> = Map::new("challenges");
+pub const CHALLENGES_COUNT: Item<u64> = Item::new("challenges_count");
+pub const TOURNAMENTS: Map<u64, Tournament> = Map::new("tournaments");
+pub const TOURNAMENTS_COUNT: Item<u64> = Item::new("tournaments_count");
+// (tournament id, round, match index) => match, the rounds start from one
+pub const TOURNAMENT_MATCHES: Map<(u64, u32, u32), TournamentMatch> =
+    Map::new("tournament_matches");
+// room id => the tournament match played in the room
+pub const TOURNAMENT_ROOMS: Map<u64, TournamentMatchRef> = Map::new("tournament_rooms");
+pub const LEAGUES: Map<u64, League> = Map::new("leagues");
+pub const LEAGUES_COUNT: Item<u64> = Item::new("leagues_count");
+// (league id, player) => the division the player plays in, the top division is zero
+pub const LEAGUE_DIVISIONS: Map<(u64, &str), u32> = Map::new("league_divisions");
+// (league id, season, player) => the standing of the registered player
+pub const LEAGUE_STANDINGS: Map<(u64, u32, &str), LeagueStanding> = Map::new("league_standings");
+// (league id, season, room id) => the division of the fixture
+pub const LEAGUE_FIXTURES: Map<(u64, u32, u64), u32> = Map::new("league_fixtures");
+// room id => the league fixture played in the room
+pub const LEAGUE_ROOMS: Map<u64, LeagueFixture> = Map::new("league_rooms");
+// sha256 of the consumed consent payloads, a consent can only start a single room.
+pub const USED_CONSENTS: Map<&[u8], Empty> = Map::new("used_consents");
+// the room keys of the consents that already started a room
+pub const USED_ROOM_KEYS: Map<&str, Empty> = Map::new("used_room_keys");
@@ -0,0 +1,254 @@
+use cosmwasm_std::{BlockInfo, Deps, Int128, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::Bound;
+
+use crate::helpers::even_duel_payouts;
+use crate::msg::{
+    GameRoomStatus, GetPlayerStatsResp, ListPlayerHistoryResp, MatchOutcome, PlayerMatchResp,
+};
+use crate::ranked::ranked_payouts;
+use crate::scoring::{scored_payouts, scored_winner};
+use crate::state::{
+    player_stats, FeeConfig, GameRoomsState, MatchRecord, PlayerStats, PLAYER_HISTORY,
+};
+use crate::teams::team_payouts;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+// adds the settled room to the stats and the history of every contestant,
+// the net profit of each is the change of their balance once the room settled.
+// `winner` is the index of the duel winner the settlement checked against the contestants.
+// the results that don't settle a room are ignored.
+pub fn record_match(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    game_room_id: u64,
+    room: &GameRoomsState,
+    result: &GameRoomStatus,
+    winner: Option<usize>,
+    fee_config: &FeeConfig,
+) -> StdResult<()> {
+    let stakes = room.stakes();
+    let net_profit = |payout: Uint128, stake: Uint128| -> StdResult<Int128> {
+        Ok(Int128::try_from(payout)?.checked_sub(Int128::try_from(stake)?)?)
+    };
+
+    let outcomes: Vec<(MatchOutcome, Int128)> = match (result, winner) {
+        // the winner of a handicapped room takes the stake of the loser minus the fees
+        (GameRoomStatus::Win { .. }, Some(winner)) if room.is_handicapped() => {
+            let loser = 1 - winner;
+            let winnings =
+                stakes[loser].checked_sub(fee_config.fee(stakes[0]) + fee_config.fee(stakes[1]))?;
+            let mut outcomes = vec![(MatchOutcome::Win, Int128::try_from(winnings)?); 2];
+            outcomes[loser] = (
+                MatchOutcome::Loss,
+                Int128::zero() - Int128::try_from(stakes[loser])?,
+            );
+            outcomes
+        }
+        // the even duels take the payouts of their settlement
+        (GameRoomStatus::Win { .. } | GameRoomStatus::Draw {}, _) if !room.is_handicapped() => {
+            let (payouts, _) = even_duel_payouts(room.prize_pool, fee_config, winner)?;
+            payouts
+                .into_iter()
+                .zip(&stakes)
+                .enumerate()
+                .map(|(i, (payout, stake))| {
+                    Ok((duel_outcome(winner, i), net_profit(payout, *stake)?))
+                })
+                .collect::<StdResult<_>>()?
+        }
+        // a handicapped or team draw costs each contestant the draw fee on their own stake
+        (GameRoomStatus::Draw {}, _) => stakes
+            .iter()
+            .map(|stake| {
+                Ok((
+                    MatchOutcome::Draw,
+                    Int128::zero() - Int128::try_from(fee_config.draw_fee(*stake))?,
+                ))
+            })
+            .collect::<StdResult<_>>()?,
+        // the losers of a scored room may take a part of the pool home
+        (GameRoomStatus::Scored { scores }, _) => {
+            let winner = scored_winner(room, scores);
+            let (payouts, _) = scored_payouts(room, scores, fee_config)?;
+            [&room.contestant1, &room.contestant2]
+                .into_iter()
+                .zip(payouts.into_iter().zip(&stakes))
+                .map(|(contestant, (payout, stake))| {
+                    let outcome = match &winner {
+                        None => MatchOutcome::Draw,
+                        Some(winner) if winner == contestant => MatchOutcome::Win,
+                        Some(_) => MatchOutcome::Loss,
+                    };
+                    Ok((outcome, net_profit(payout, *stake)?))
+                })
+                .collect::<StdResult<_>>()?
+        }
+        // only the first of the ranking wins, the others may still take a share of the pool
+        (GameRoomStatus::Ranked { ranking }, _) => {
+            let payouts = ranked_payouts(room, ranking, fee_config)?;
+            room.contestants()
+                .into_iter()
+                .zip(&stakes)
+                .map(|(contestant, stake)| {
+                    let rank = ranking.iter().position(|player| player == contestant);
+                    let outcome = match rank {
+                        Some(0) => MatchOutcome::Win,
+                        _ => MatchOutcome::Loss,
+                    };
+                    let payout = rank.map(|rank| payouts[rank]).unwrap_or_default();
+                    Ok((outcome, net_profit(payout, *stake)?))
+                })
+                .collect::<StdResult<_>>()?
+        }
+        // the winning side shares the stakes of the losing one
+        (GameRoomStatus::TeamWin { team }, _) => {
+            let shares = team_payouts(room, *team, fee_config)?;
+            room.teams
+                .iter()
+                .zip(shares.into_iter().zip(&stakes))
+                .map(|(member_team, (share, stake))| match member_team == team {
+                    true => Ok((MatchOutcome::Win, Int128::try_from(share)?)),
+                    false => Ok((
+                        MatchOutcome::Loss,
+                        Int128::zero() - Int128::try_from(*stake)?,
+                    )),
+                })
+                .collect::<StdResult<_>>()?
+        }
+        _ => return Ok(()),
+    };
+
+    // each contestant pays the fee on their own stake, the draws only if the schedule says so
+    let fees = outcomes
+        .iter()
+        .zip(&stakes)
+        .map(|((outcome, _), stake)| match outcome {
+            MatchOutcome::Draw => fee_config.draw_fee(*stake),
+            _ => fee_config.fee(*stake),
+        })
+        .collect();
+    record_outcomes(storage, block, game_room_id, room, outcomes, fees)
+}
+
+// the tournament and league rooms hold no stakes, only the outcome of the match is recorded
+pub fn record_stakeless_match(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    game_room_id: u64,
+    room: &GameRoomsState,
+    winner: Option<usize>,
+) -> StdResult<()> {
+    let outcomes = (0..2)
+        .map(|i| (duel_outcome(winner, i), Int128::zero()))
+        .collect();
+    record_outcomes(
+        storage,
+        block,
+        game_room_id,
+        room,
+        outcomes,
+        vec![Uint128::zero(); 2],
+    )
+}
+
+fn duel_outcome(winner: Option<usize>, contestant: usize) -> MatchOutcome {
+    match winner {
+        None => MatchOutcome::Draw,
+        Some(winner) if winner == contestant => MatchOutcome::Win,
+        Some(_) => MatchOutcome::Loss,
+    }
+}
+
+// `outcomes` and `fees` are in the order of `contestants`
+fn record_outcomes(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    game_room_id: u64,
+    room: &GameRoomsState,
+    outcomes: Vec<(MatchOutcome, Int128)>,
+    fees: Vec<Uint128>,
+) -> StdResult<()> {
+    let contestants = room.contestants();
+    for (i, (((player, (outcome, net_profit)), stake), fee)) in contestants
+        .iter()
+        .zip(outcomes)
+        .zip(room.stakes())
+        .zip(fees)
+        .enumerate()
+    {
+        // everyone else of the room, a team room is played against the other side only
+        let opponents: Vec<String> = contestants
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i && (!room.is_team() || room.teams[*j] != room.teams[i]))
+            .map(|(_, opponent)| opponent.to_string())
+            .collect();
+        let (opponent, opponents) = match opponents.as_slice() {
+            [opponent] => (opponent.clone(), vec![]),
+            _ => (String::new(), opponents),
+        };
+
+        player_stats().update(storage, player, |stats| -> StdResult<_> {
+            let mut stats: PlayerStats = stats.unwrap_or_default();
+            stats.games_played += 1;
+            match outcome {
+                MatchOutcome::Win => stats.wins += 1,
+                MatchOutcome::Loss => stats.losses += 1,
+                MatchOutcome::Draw => stats.draws += 1,
+            }
+            stats.total_wagered = stats.total_wagered.checked_add(stake)?;
+            stats.net_profit = stats.net_profit.checked_add(net_profit)?;
+            stats.fees_paid = stats.fees_paid.checked_add(fee)?;
+            Ok(stats)
+        })?;
+        PLAYER_HISTORY.save(
+            storage,
+            (player, game_room_id),
+            &MatchRecord {
+                opponent,
+                opponents,
+                stake,
+                outcome,
+                net_profit,
+                fee,
+                finished_at: block.time,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+pub fn query_player_stats(deps: Deps, player: String) -> StdResult<GetPlayerStatsResp> {
+    let stats = player_stats()
+        .may_load(deps.storage, &player)?
+        .unwrap_or_default();
+    Ok(GetPlayerStatsResp { stats })
+}
+
+// the settled rooms of the player, the most recent first.
+pub fn query_player_history(
+    deps: Deps,
+    player: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListPlayerHistoryResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_after.map(Bound::exclusive);
+
+    let matches = PLAYER_HISTORY
+        .prefix(&player)
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(game_room_id, record)| PlayerMatchResp {
+                game_room_id,
+                record,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListPlayerHistoryResp { matches })
+}
@@ -0,0 +1,177 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128,
+};
+
+use crate::contract::execute::{ensure_valid_stake, room_deadline, save_new_game_room};
+use crate::error::ContractError;
+use crate::fees::{credit_fees, load_fee_config};
+use crate::msg::{CreateGameRoomResp, GameRoomStatus, Role, TeamMember};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::roles::ensure_role;
+use crate::state::{game_rooms_state, Balance, FeeConfig, GameRoomsState, BALANCES};
+
+pub const MAX_TEAM_SIZE: u32 = 5;
+
+pub fn execute_create_team_room(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    team1: Vec<TeamMember>,
+    team2: Vec<TeamMember>,
+) -> Result<Response, ContractError> {
+    // sender must be a game operator
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let mut players: Vec<&String> = team1
+        .iter()
+        .chain(&team2)
+        .map(|member| &member.addr)
+        .collect();
+    players.sort();
+    players.dedup();
+    if [&team1, &team2]
+        .iter()
+        .any(|team| !(1..=MAX_TEAM_SIZE).contains(&(team.len() as u32)))
+        || players.len() != team1.len() + team2.len()
+    {
+        return Err(ContractError::InvalidTeams { max: MAX_TEAM_SIZE });
+    }
+    for member in team1.iter().chain(&team2) {
+        ensure_valid_stake(deps.storage, member.stake)?;
+    }
+    // whichever side loses, its stakes must cover the fee of every member
+    let fee_config = load_fee_config(deps.storage, None)?;
+    let total_fee: Uint128 = team1
+        .iter()
+        .chain(&team2)
+        .map(|member| fee_config.fee(member.stake))
+        .sum();
+    for team in [&team1, &team2] {
+        if team.iter().map(|member| member.stake).sum::<Uint128>() < total_fee {
+            return Err(ContractError::InvalidStake {});
+        }
+    }
+
+    let members: Vec<&TeamMember> = team1.iter().chain(&team2).collect();
+    // like the duels nothing is locked until every member joined
+    let game_room = GameRoomsState {
+        contestant1: members[0].addr.clone(),
+        contestant2: members[1].addr.clone(),
+        prize_pool: members.iter().map(|member| member.stake).sum(),
+        status: GameRoomStatus::Pending { joined: vec![] },
+        created_at: env.block.time,
+        created_height: env.block.height,
+        deadline: room_deadline(deps.storage, &env.block)?,
+        secret_hash: None,
+        extra_contestants: members[2..]
+            .iter()
+            .map(|member| member.addr.clone())
+            .collect(),
+        payout_table: vec![],
+        series: None,
+        stakes: members.iter().map(|member| member.stake).collect(),
+        payout_policy: Default::default(),
+        teams: [vec![1; team1.len()], vec![2; team2.len()]].concat(),
+        fee_config: Some(fee_config),
+    };
+    let game_room_id = save_new_game_room(deps.storage, &game_room)?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&CreateGameRoomResp(game_room_id))?)
+        .add_attributes(vec![
+            attr("action", "create_team_room"),
+            attr("room_id", game_room_id.to_string()),
+            attr("team1", team1.len().to_string()),
+            attr("team2", team2.len().to_string()),
+        ]))
+}
+
+// what each member wins on top of their own stake in the order of `contestants`.
+// every member pays the fee out of the stakes of the losing side, the rest goes to the
+// winning side pro rata to the stakes and the rounding dust goes to its first member.
+pub fn team_payouts(
+    game_room: &GameRoomsState,
+    team: u8,
+    fee_config: &FeeConfig,
+) -> StdResult<Vec<Uint128>> {
+    let members: Vec<(Uint128, bool)> = game_room
+        .stakes()
+        .into_iter()
+        .zip(&game_room.teams)
+        .map(|(stake, member_team)| (stake, *member_team == team))
+        .collect();
+    let total_fee: Uint128 = members
+        .iter()
+        .map(|(stake, _)| fee_config.fee(*stake))
+        .sum();
+    let winning_stakes: Uint128 = members
+        .iter()
+        .filter(|(_, won)| *won)
+        .map(|(stake, _)| *stake)
+        .sum();
+    let pot = members
+        .iter()
+        .filter(|(_, won)| !*won)
+        .map(|(stake, _)| *stake)
+        .sum::<Uint128>()
+        .checked_sub(total_fee)?;
+
+    let mut shares: Vec<Uint128> = members
+        .iter()
+        .map(|(stake, won)| match won {
+            true => pot.multiply_ratio(*stake, winning_stakes),
+            false => Uint128::zero(),
+        })
+        .collect();
+    let paid = shares.iter().sum::<Uint128>();
+    if let Some(first) = members.iter().position(|(_, won)| *won) {
+        shares[first] += pot - paid;
+    }
+    Ok(shares)
+}
+
+pub fn settle_team_room(
+    storage: &mut dyn Storage,
+    game_room_id: u64,
+    game_room: &GameRoomsState,
+    team: u8,
+    fee_config: &FeeConfig,
+) -> Result<(), ContractError> {
+    if !game_room.teams.contains(&team) {
+        return Err(ContractError::InvalidRoomResult {});
+    }
+
+    let shares = team_payouts(game_room, team, fee_config)?;
+    let mut fees = vec![];
+    for (((member, stake), member_team), share) in game_room
+        .contestants()
+        .into_iter()
+        .zip(game_room.stakes())
+        .zip(&game_room.teams)
+        .zip(shares)
+    {
+        BALANCES.update(
+            storage,
+            &Addr::unchecked(member),
+            |balance: Option<Balance>| -> StdResult<_> {
+                let mut balance = balance.unwrap_or_default();
+                Ok(match *member_team == team {
+                    true => balance
+                        .unlock_and_decrease(stake, Uint128::zero())
+                        .total_increase(share),
+                    false => balance.unlock_and_decrease(stake, stake),
+                })
+            },
+        )?;
+        fees.push((member.as_str(), fee_config.fee(stake)));
+    }
+    credit_fees(storage, &fees)?;
+
+    game_rooms_state().save(
+        storage,
+        game_room_id,
+        &game_room.get_finish_state(GameRoomStatus::TeamWin { team }),
+    )?;
+    Ok(())
+}
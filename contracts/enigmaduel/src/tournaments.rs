@@ -0,0 +1,508 @@
+use cosmwasm_std::{
+    attr, Addr, BlockInfo, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+    Storage, Uint128,
+};
+use cw_storage_plus::Bound;
+
+use crate::contract::execute::{
+    ensure_valid_stake, lock_stake, room_deadline, save_new_game_room, unlock_stake,
+};
+use crate::error::ContractError;
+use crate::fees::{credit_fees, load_fee_config};
+use crate::helpers::duel_winner;
+use crate::msg::{
+    GameRoomStatus, GetTournamentBracketResp, ListTournamentsResp, Role, TournamentMatchResp,
+    TournamentResp,
+};
+use crate::pause::{ensure_not_paused, Operation};
+use crate::ratings::{load_rating, update_ratings};
+use crate::roles::ensure_role;
+use crate::state::{
+    game_rooms_state, Balance, GameRoomsState, Tournament, TournamentMatch, TournamentMatchRef,
+    TournamentStatus, BALANCES, TOURNAMENTS, TOURNAMENTS_COUNT, TOURNAMENT_MATCHES,
+    TOURNAMENT_ROOMS,
+};
+use crate::stats::record_stakeless_match;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub const MIN_TOURNAMENT_PLAYERS: u32 = 2;
+pub const MAX_TOURNAMENT_PLAYERS: u32 = 64;
+// the payout table shares add up to the whole pool
+const TOTAL_SHARES: u16 = 10_000;
+
+pub fn execute_create_tournament(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    entry_fee: Uint128,
+    max_players: u32,
+    payout_table: Vec<u16>,
+) -> Result<Response, ContractError> {
+    // sender must be a game operator
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    if !(MIN_TOURNAMENT_PLAYERS..=MAX_TOURNAMENT_PLAYERS).contains(&max_players) {
+        return Err(ContractError::InvalidTournamentSize {
+            min: MIN_TOURNAMENT_PLAYERS,
+            max: MAX_TOURNAMENT_PLAYERS,
+        });
+    }
+    if payout_table.is_empty()
+        || payout_table.iter().map(|share| *share as u32).sum::<u32>() != TOTAL_SHARES as u32
+    {
+        return Err(ContractError::InvalidPayoutTable {});
+    }
+    ensure_valid_stake(deps.storage, entry_fee)?;
+    // the players register for the fee of today, a later config update doesn't touch the tournament
+    let fee_config = load_fee_config(deps.storage, None)?;
+    let deadline = room_deadline(deps.storage, &env.block)?;
+
+    let tournament_id = TOURNAMENTS_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    TOURNAMENTS_COUNT.save(deps.storage, &tournament_id)?;
+    TOURNAMENTS.save(
+        deps.storage,
+        tournament_id,
+        &Tournament {
+            name,
+            entry_fee,
+            max_players,
+            payout_table,
+            players: vec![],
+            rounds: 0,
+            status: TournamentStatus::Registration {},
+            fee_config: Some(fee_config),
+            deadline: Some(deadline),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_tournament"),
+        attr("tournament_id", tournament_id.to_string()),
+        attr("entry_fee", entry_fee),
+        attr("max_players", max_players.to_string()),
+    ]))
+}
+
+pub fn execute_register_for_tournament(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let mut tournament = TOURNAMENTS.load(deps.storage, tournament_id)?;
+    if tournament.status != (TournamentStatus::Registration {}) {
+        return Err(ContractError::TournamentNotOpen {});
+    }
+    let player = info.sender.to_string();
+    if tournament.players.contains(&player) {
+        return Err(ContractError::AlreadyRegistered {});
+    }
+    if tournament.players.len() as u32 >= tournament.max_players {
+        return Err(ContractError::TournamentFull {});
+    }
+
+    // the entry fee stays locked until the tournament is over
+    lock_stake(deps.storage, &info.sender, tournament.entry_fee)?;
+    tournament.players.push(player.clone());
+    tournament.deadline = Some(room_deadline(deps.storage, &env.block)?);
+    TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_for_tournament"),
+        attr("tournament_id", tournament_id.to_string()),
+        attr("player", player),
+    ]))
+}
+
+// seeds the players by rating and opens the rooms of the first round,
+// the best seeds get the byes when the players don't fill the bracket.
+pub fn execute_start_tournament(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    ensure_not_paused(deps.storage, Operation::RoomCreation)?;
+
+    let mut tournament = TOURNAMENTS.load(deps.storage, tournament_id)?;
+    if tournament.status != (TournamentStatus::Registration {}) {
+        return Err(ContractError::TournamentNotOpen {});
+    }
+    if (tournament.players.len() as u32) < MIN_TOURNAMENT_PLAYERS {
+        return Err(ContractError::NotEnoughPlayers {});
+    }
+
+    // the registration order breaks the rating ties
+    let mut seeds = tournament
+        .players
+        .iter()
+        .map(|player| Ok((load_rating(deps.storage, player)?, player.clone())))
+        .collect::<StdResult<Vec<_>>>()?;
+    seeds.sort_by_key(|(rating, _)| std::cmp::Reverse(*rating));
+
+    let size = seeds.len().next_power_of_two();
+    tournament.rounds = size.trailing_zeros();
+    tournament.status = TournamentStatus::Running {};
+    tournament.deadline = Some(room_deadline(deps.storage, &env.block)?);
+    TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+    let order = bracket_order(size);
+    for (index, pair) in order.chunks(2).enumerate() {
+        let player = |seed: usize| seeds.get(seed - 1).map(|(_, player)| player.clone());
+        create_match(
+            deps.storage,
+            &env.block,
+            TournamentMatchRef {
+                tournament_id,
+                round: 1,
+                index: index as u32,
+            },
+            player(pair[0]).unwrap(),
+            player(pair[1]),
+        )?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "start_tournament"),
+        attr("tournament_id", tournament_id.to_string()),
+        attr("players", seeds.len().to_string()),
+        attr("rounds", tournament.rounds.to_string()),
+    ]))
+}
+
+// gives the entry fees back, the rooms still waiting for a winner expire.
+// anyone can cancel a tournament the operators stopped running past its deadline.
+pub fn execute_cancel_tournament(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    let mut tournament = TOURNAMENTS.load(deps.storage, tournament_id)?;
+    let past_deadline = matches!(tournament.deadline, Some(deadline) if env.block.time >= deadline);
+    if !past_deadline {
+        ensure_role(deps.as_ref(), &info, Role::Operator)?;
+    }
+    match tournament.status {
+        TournamentStatus::Registration {} | TournamentStatus::Running {} => {}
+        _ => return Err(ContractError::TournamentNotRunning {}),
+    }
+
+    for player in &tournament.players {
+        unlock_stake(deps.storage, &Addr::unchecked(player), tournament.entry_fee)?;
+    }
+    let game_room_ids = TOURNAMENT_MATCHES
+        .sub_prefix(tournament_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((_, tournament_match)) if tournament_match.winner.is_none() => {
+                tournament_match.game_room_id.map(Ok)
+            }
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for game_room_id in game_room_ids {
+        let game_room = game_rooms_state().load(deps.storage, game_room_id)?;
+        game_rooms_state().save(
+            deps.storage,
+            game_room_id,
+            &game_room.get_finish_state(GameRoomStatus::Expired {}),
+        )?;
+    }
+    tournament.status = TournamentStatus::Cancelled {};
+    TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_tournament"),
+        attr("tournament_id", tournament_id.to_string()),
+    ]))
+}
+
+// settles a tournament room, the winner moves on to the next round.
+pub fn finish_tournament_match(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    game_room_id: u64,
+    game_room: &GameRoomsState,
+    match_ref: TournamentMatchRef,
+    result: GameRoomStatus,
+) -> Result<Response, ContractError> {
+    let winner = match &result {
+        GameRoomStatus::Win { addr } => addr.clone(),
+        _ => return Err(ContractError::TournamentMatchNeedsWinner {}),
+    };
+    let winner_index = duel_winner(game_room, &winner)?;
+    let mut tournament = TOURNAMENTS.load(storage, match_ref.tournament_id)?;
+    if tournament.status != (TournamentStatus::Running {}) {
+        return Err(ContractError::TournamentNotRunning {});
+    }
+    tournament.deadline = Some(room_deadline(storage, block)?);
+    TOURNAMENTS.save(storage, match_ref.tournament_id, &tournament)?;
+
+    record_stakeless_match(storage, block, game_room_id, game_room, Some(winner_index))?;
+    update_ratings(storage, block, game_room_id, game_room, &result)?;
+    game_rooms_state().save(storage, game_room_id, &game_room.get_finish_state(result))?;
+    set_match_winner(storage, block, &match_ref, winner.clone())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "finish_game_room"),
+        attr("room_id", game_room_id.to_string()),
+        attr("tournament_id", match_ref.tournament_id.to_string()),
+        attr("round", match_ref.round.to_string()),
+        attr("winner", winner),
+    ]))
+}
+
+// the seeds of a bracket in match order, the best seeds meet as late as possible.
+fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let next = order.len() * 2 + 1;
+        order = order.iter().flat_map(|seed| [*seed, next - seed]).collect();
+    }
+    order
+}
+
+// opens the room of the match, a missing opponent is a bye.
+fn create_match(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    match_ref: TournamentMatchRef,
+    player1: String,
+    player2: Option<String>,
+) -> StdResult<()> {
+    let key = (match_ref.tournament_id, match_ref.round, match_ref.index);
+    let Some(player2) = player2 else {
+        TOURNAMENT_MATCHES.save(
+            storage,
+            key,
+            &TournamentMatch {
+                player1: player1.clone(),
+                player2: None,
+                game_room_id: None,
+                winner: None,
+            },
+        )?;
+        return set_match_winner(storage, block, &match_ref, player1);
+    };
+
+    // the entry fees are the stakes, the room itself holds nothing
+    let game_room = GameRoomsState {
+        contestant1: player1.clone(),
+        contestant2: player2.clone(),
+        prize_pool: Uint128::zero(),
+        status: GameRoomStatus::Started {},
+        created_at: block.time,
+        created_height: block.height,
+        deadline: room_deadline(storage, block)?,
+        secret_hash: None,
+        extra_contestants: vec![],
+        payout_table: vec![],
+        series: None,
+        stakes: vec![],
+        payout_policy: Default::default(),
+        teams: vec![],
+        fee_config: None,
+    };
+    let game_room_id = save_new_game_room(storage, &game_room)?;
+    TOURNAMENT_ROOMS.save(storage, game_room_id, &match_ref)?;
+    TOURNAMENT_MATCHES.save(
+        storage,
+        key,
+        &TournamentMatch {
+            player1,
+            player2: Some(player2),
+            game_room_id: Some(game_room_id),
+            winner: None,
+        },
+    )
+}
+
+fn set_match_winner(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    match_ref: &TournamentMatchRef,
+    winner: String,
+) -> StdResult<()> {
+    let tournament_id = match_ref.tournament_id;
+    TOURNAMENT_MATCHES.update(
+        storage,
+        (tournament_id, match_ref.round, match_ref.index),
+        |tournament_match| -> StdResult<_> {
+            let mut tournament_match =
+                tournament_match.ok_or_else(|| StdError::not_found("TournamentMatch"))?;
+            tournament_match.winner = Some(winner.clone());
+            Ok(tournament_match)
+        },
+    )?;
+
+    let tournament = TOURNAMENTS.load(storage, tournament_id)?;
+    if match_ref.round == tournament.rounds {
+        return pay_out_tournament(storage, tournament_id, tournament, winner);
+    }
+
+    // the next match starts once the winner of the neighbour match is known too,
+    // which may not even exist yet while the first round is being laid out
+    let sibling = TOURNAMENT_MATCHES.may_load(
+        storage,
+        (tournament_id, match_ref.round, match_ref.index ^ 1),
+    )?;
+    if let Some(other) = sibling.and_then(|sibling| sibling.winner) {
+        let (player1, player2) = if match_ref.index.is_multiple_of(2) {
+            (winner, other)
+        } else {
+            (other, winner)
+        };
+        create_match(
+            storage,
+            block,
+            TournamentMatchRef {
+                tournament_id,
+                round: match_ref.round + 1,
+                index: match_ref.index / 2,
+            },
+            player1,
+            Some(player2),
+        )?;
+    }
+    Ok(())
+}
+
+// every player pays the fee, the rest of the pool is split by the payout table:
+// the first share goes to the champion and each following share to the players
+// knocked out one round earlier, split evenly. the undistributed shares and the
+// rounding dust go to the champion.
+fn pay_out_tournament(
+    storage: &mut dyn Storage,
+    tournament_id: u64,
+    mut tournament: Tournament,
+    champion: String,
+) -> StdResult<()> {
+    let fee = match &tournament.fee_config {
+        Some(fee_config) => fee_config.fee(tournament.entry_fee),
+        None => load_fee_config(storage, None)?.fee(tournament.entry_fee),
+    };
+    let players = tournament.players.len() as u128;
+    let total_fee = fee * Uint128::new(players);
+    let pool = (tournament.entry_fee * Uint128::new(players)).checked_sub(total_fee)?;
+
+    let mut payouts: Vec<(String, Uint128)> = tournament
+        .players
+        .iter()
+        .map(|player| (player.clone(), Uint128::zero()))
+        .collect();
+
+    let champion_share = pool.multiply_ratio(tournament.payout_table[0], TOTAL_SHARES);
+    add_payout(&mut payouts, &champion, champion_share);
+    for round in (1..=tournament.rounds).rev() {
+        let place = (tournament.rounds - round + 1) as usize;
+        let Some(share) = tournament.payout_table.get(place) else {
+            break;
+        };
+        let losers = TOURNAMENT_MATCHES
+            .prefix((tournament_id, round))
+            .range(storage, None, None, Order::Ascending)
+            .filter_map(|item| match item {
+                Ok((_, tournament_match)) => tournament_match.loser().map(Ok),
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        if losers.is_empty() {
+            continue;
+        }
+        let each = pool.multiply_ratio(*share, TOTAL_SHARES) / Uint128::new(losers.len() as u128);
+        for loser in &losers {
+            add_payout(&mut payouts, loser, each);
+        }
+    }
+    let paid = payouts.iter().map(|(_, payout)| *payout).sum::<Uint128>();
+    add_payout(&mut payouts, &champion, pool - paid);
+
+    for (player, payout) in payouts {
+        BALANCES.update(
+            storage,
+            &Addr::unchecked(player),
+            |balance: Option<Balance>| -> StdResult<_> {
+                Ok(balance
+                    .unwrap_or_default()
+                    .unlock_and_decrease(tournament.entry_fee, tournament.entry_fee)
+                    .total_increase(payout))
+            },
+        )?;
+    }
+    credit_fees(
+        storage,
+        &tournament
+            .players
+            .iter()
+            .map(|player| (player.as_str(), fee))
+            .collect::<Vec<_>>(),
+    )?;
+
+    tournament.status = TournamentStatus::Finished { champion };
+    TOURNAMENTS.save(storage, tournament_id, &tournament)
+}
+
+fn add_payout(payouts: &mut [(String, Uint128)], player: &str, amount: Uint128) {
+    if let Some((_, payout)) = payouts.iter_mut().find(|(p, _)| p == player) {
+        *payout += amount;
+    }
+}
+
+pub fn query_tournament(deps: Deps, tournament_id: u64) -> StdResult<Tournament> {
+    TOURNAMENTS.load(deps.storage, tournament_id)
+}
+
+// the matches of every round, the first round first
+pub fn query_tournament_bracket(
+    deps: Deps,
+    tournament_id: u64,
+) -> StdResult<GetTournamentBracketResp> {
+    let matches = TOURNAMENT_MATCHES
+        .sub_prefix(tournament_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|((round, index), tournament_match)| TournamentMatchResp {
+                round,
+                index,
+                tournament_match,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(GetTournamentBracketResp { matches })
+}
+
+pub fn query_tournaments(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListTournamentsResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let tournaments = TOURNAMENTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(tournament_id, tournament)| TournamentResp {
+                tournament_id,
+                tournament,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListTournamentsResp { tournaments })
+}
@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::let_unit_value, clippy::inconsistent_digit_grouping)]
 mod tests {
 
     use crate::*;
@@ -9,12 +10,13 @@ mod tests {
     use msg::{
         CollectFeesParams, GameRoomFinishParams, GameRoomIntiParams, GameRoomStatus, InstantiateMsg,
     };
-    use state::GameRoomsState;
+    use state::{FeeLedger, GameRoomsState};
 
     struct MockApp {
         app: App,
         edt_addr: Addr,
         enigma_addr: Addr,
+        enigma_code_id: u64,
     }
 
     pub const ENIGMA_ADMIN: &str = "addr0000";
@@ -23,6 +25,7 @@ mod tests {
     pub const USER1: &str = "addr3333";
     pub const USER2: &str = "addr4444";
     pub const USER3: &str = "addr5555";
+    pub const USER4: &str = "addr6666";
 
     fn get_app() -> MockApp {
         let mut app = App::new(|router, _, storage| {
@@ -69,7 +72,8 @@ mod tests {
             .unwrap();
 
         let enigma_code =
-            ContractWrapper::new(contract::execute, contract::instantiate, contract::query); // the code that is going to be saved on chain
+            ContractWrapper::new(contract::execute, contract::instantiate, contract::query)
+                .with_migrate(contract::migrate); // the code that is going to be saved on chain
         let enigma_code_id = app.store_code(Box::new(enigma_code));
         let enigma_addr = app
             .instantiate_contract(
@@ -79,6 +83,7 @@ mod tests {
                     fee: Uint128::new(100000000),
                     admin: ENIGMA_ADMIN.into(),
                     enigma_token_duel: edt_addr.clone().to_string(),
+                    room_timeout: None,
                 },
                 &[coin(2, "eth")],
                 "enigma",
@@ -90,6 +95,7 @@ mod tests {
             app,
             edt_addr,
             enigma_addr,
+            enigma_code_id,
         }
     }
 
@@ -143,7 +149,7 @@ mod tests {
             .unwrap();
     }
 
-    fn create_gr(app: &mut MockApp) -> String {
+    fn create_gr(app: &mut MockApp) -> u64 {
         match app.app.execute_contract(
             Addr::unchecked(ENIGMA_ADMIN),
             app.enigma_addr.clone(),
@@ -153,29 +159,48 @@ mod tests {
                     contestant2: USER2.into(),
                     prize_pool: Uint128::new(1500000000),
                     status: msg::GameRoomStatus::Started {},
+                    consents: None,
+                    payout_policy: None,
+                    fee_config: None,
                 },
             },
             &[],
         ) {
             Ok(res) => {
                 println!("{:?}", res.events[1].attributes[2].value);
-                res.events[1].attributes[2].value.clone()
-            }
-            Err(err) => {
-                println!("error: {}", err);
-                err.to_string()
+                let game_room_id = res.events[1].attributes[2].value.parse().unwrap();
+
+                // both contestants agree to the stake
+                join_gr(app, USER1, game_room_id);
+                join_gr(app, USER2, game_room_id);
+                game_room_id
             }
+            Err(err) => panic!("error: {}", err),
         }
     }
 
-    fn finish_gr(app: &mut MockApp, game_room_key: String, result: GameRoomStatus) {
+    fn join_gr(app: &mut MockApp, user: &str, game_room_id: u64) {
+        app.app
+            .execute_contract(
+                Addr::unchecked(user),
+                app.enigma_addr.clone(),
+                &crate::msg::ExecuteMsg::JoinRoom {
+                    game_room_id,
+                    secret: None,
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn finish_gr(app: &mut MockApp, game_room_id: u64, result: GameRoomStatus) {
         app.app
             .execute_contract(
                 Addr::unchecked(ENIGMA_ADMIN),
                 app.enigma_addr.clone(),
                 &crate::msg::ExecuteMsg::FinishGameRoom {
                     game_room_finish_params: GameRoomFinishParams {
-                        game_room_key,
+                        game_room_id,
                         result,
                     },
                 },
@@ -184,6 +209,84 @@ mod tests {
             .unwrap();
     }
 
+    // both of the funded users deposited
+    fn funded_app() -> MockApp {
+        let mut app = get_app();
+        for user in [USER1, USER2] {
+            increase_allowance(&mut app, user);
+            deposit(&mut app, user);
+        }
+        app
+    }
+
+    fn query_balance(app: &MockApp, user: &str) -> Uint128 {
+        app.app
+            .wrap()
+            .query_wasm_smart(
+                app.enigma_addr.clone(),
+                &msg::QueryMsg::GetUserBalance { user: user.into() },
+            )
+            .unwrap()
+    }
+
+    fn query_collected_fees(app: &MockApp) -> Uint128 {
+        app.app
+            .wrap()
+            .query_wasm_smart(app.enigma_addr.clone(), &msg::QueryMsg::GetCollectedFees {})
+            .unwrap()
+    }
+
+    fn locked(app: &MockApp, user: &str) -> Uint128 {
+        app.app
+            .wrap()
+            .query_wasm_smart(
+                app.enigma_addr.clone(),
+                &msg::QueryMsg::GetUserLockedBalance { user: user.into() },
+            )
+            .unwrap()
+    }
+
+    fn assert_err(err: anyhow::Error, expected: ContractError) {
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    fn query_stats(app: &MockApp, player: &str) -> state::PlayerStats {
+        let resp: msg::GetPlayerStatsResp = app
+            .app
+            .wrap()
+            .query_wasm_smart(
+                app.enigma_addr.clone(),
+                &msg::QueryMsg::GetPlayerStats {
+                    player: player.into(),
+                },
+            )
+            .unwrap();
+        resp.stats
+    }
+
+    // the ids of the rooms the player is a contestant of
+    fn rooms_of(app: &MockApp, player: &str) -> Vec<u64> {
+        let resp: msg::ListGameRoomsResp = app
+            .app
+            .wrap()
+            .query_wasm_smart(
+                app.enigma_addr.clone(),
+                &msg::QueryMsg::ListRoomsByPlayer {
+                    player: player.into(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        resp.rooms
+            .into_iter()
+            .map(|room| room.game_room_id)
+            .collect()
+    }
+
     fn collect_fees(app: &mut MockApp, receiver: String, amount: Uint128) {
         app.app
             .execute_contract(
@@ -197,6 +300,74 @@ mod tests {
             .unwrap();
     }
 
+    fn execute(app: &mut MockApp, sender: &str, msg: crate::msg::ExecuteMsg) -> anyhow::Result<()> {
+        app.app
+            .execute_contract(Addr::unchecked(sender), app.enigma_addr.clone(), &msg, &[])
+            .map(|_| ())
+    }
+
+    fn try_finish_gr(
+        app: &mut MockApp,
+        game_room_id: u64,
+        result: GameRoomStatus,
+    ) -> anyhow::Result<()> {
+        execute(
+            app,
+            ENIGMA_ADMIN,
+            crate::msg::ExecuteMsg::FinishGameRoom {
+                game_room_finish_params: GameRoomFinishParams {
+                    game_room_id,
+                    result,
+                },
+            },
+        )
+    }
+
+    fn query_room(app: &MockApp, game_room_id: u64) -> GameRoomsState {
+        app.app
+            .wrap()
+            .query_wasm_smart(
+                app.enigma_addr.clone(),
+                &msg::QueryMsg::GetGameRoomState { game_room_id },
+            )
+            .unwrap()
+    }
+
+    // hands some edt to the users that didn't get any on instantiation
+    fn fund(app: &mut MockApp, user: &str) {
+        app.app
+            .execute_contract(
+                Addr::unchecked(USER1),
+                app.edt_addr.clone(),
+                &test_edt::msg::ExecuteMsg::Transfer {
+                    recipient: user.into(),
+                    amount: Uint128::new(1000000000),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    // every contestant deposits, `create` opens the room 1 and the contestants join it
+    fn setup<T>(
+        contestants: &[&str],
+        create: impl FnOnce(&mut MockApp) -> anyhow::Result<T>,
+    ) -> MockApp {
+        let mut app = get_app();
+        for user in contestants {
+            if !matches!(*user, USER1 | USER2) {
+                fund(&mut app, user);
+            }
+            increase_allowance(&mut app, user);
+            deposit(&mut app, user);
+        }
+        create(&mut app).unwrap();
+        for user in contestants {
+            join_gr(&mut app, user, 1);
+        }
+        app
+    }
+
     #[test]
     fn test_deposit() {
         let mut app = get_app();
@@ -270,14 +441,14 @@ mod tests {
         increase_allowance(&mut app, USER2);
         deposit(&mut app, USER2);
 
-        let game_room_key = create_gr(&mut app);
+        let game_room_id = create_gr(&mut app);
 
         let gr_state: Option<GameRoomsState> = app
             .app
             .wrap()
             .query_wasm_smart(
                 app.enigma_addr.clone(),
-                &msg::QueryMsg::GetGameRoomState { game_room_key },
+                &msg::QueryMsg::GetGameRoomState { game_room_id },
             )
             .unwrap();
         let gr_count: Option<Uint256> = app
@@ -318,11 +489,11 @@ mod tests {
         increase_allowance(&mut app, USER2);
         deposit(&mut app, USER2);
 
-        let game_room_key = create_gr(&mut app);
+        let game_room_id = create_gr(&mut app);
 
         let _ = finish_gr(
             &mut app,
-            game_room_key.clone(),
+            game_room_id,
             GameRoomStatus::Win { addr: USER1.into() },
         );
         let con_1_bal: Option<Uint128> = app
@@ -347,7 +518,7 @@ mod tests {
             .wrap()
             .query_wasm_smart(
                 app.enigma_addr.clone(),
-                &msg::QueryMsg::GetGameRoomState { game_room_key },
+                &msg::QueryMsg::GetGameRoomState { game_room_id },
             )
             .unwrap();
 
@@ -356,8 +527,8 @@ mod tests {
             .wrap()
             .query_wasm_smart(app.enigma_addr.clone(), &msg::QueryMsg::GetCollectedFees {})
             .unwrap();
-        // the user one wins so the the balance must be the prize pool - fee + old balance => 1_500_000_000 - 1_00_000_000 + 1_000_000_000 = 2_400_000_000
-        assert_eq!(con_1_bal.unwrap(), Uint128::new(2400000000));
+        // the user one wins so the balance must be the old balance + the stake of the loser - the fees of both => 1_000_000_000 + 750_000_000 - 200_000_000 = 1_550_000_000
+        assert_eq!(con_1_bal.unwrap(), Uint128::new(1550000000));
         // the user two lost the game so the balance must be => old balance - prize pool / 2  = 1_000_000_00 - 750_000_000 = 250_000_000
         assert_eq!(con_2_bal.unwrap(), Uint128::new(250000000));
         assert_eq!(collected_fees.unwrap(), Uint128::new(2_00_000_000));
@@ -368,6 +539,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_finish_game_room_win_conserves_deposits() {
+        let mut app = funded_app();
+
+        let game_room_id = create_gr(&mut app);
+        finish_gr(
+            &mut app,
+            game_room_id,
+            GameRoomStatus::Win { addr: USER2.into() },
+        );
+
+        let ledger: FeeLedger = app
+            .app
+            .wrap()
+            .query_wasm_smart(app.enigma_addr.clone(), &msg::QueryMsg::GetFeeLedger {})
+            .unwrap();
+        // nothing is minted by the settlement, the deposits only move between the players and the fees
+        assert_eq!(
+            query_balance(&app, USER1) + query_balance(&app, USER2) + ledger.available(),
+            Uint128::new(2_000_000_000)
+        );
+        assert_eq!(locked(&app, USER1) + locked(&app, USER2), Uint128::zero());
+    }
+
+    #[test]
+    fn test_finish_game_room_win_by_stranger() {
+        let mut app = funded_app();
+
+        let game_room_id = create_gr(&mut app);
+        let err = app
+            .app
+            .execute_contract(
+                Addr::unchecked(ENIGMA_ADMIN),
+                app.enigma_addr.clone(),
+                &crate::msg::ExecuteMsg::FinishGameRoom {
+                    game_room_finish_params: GameRoomFinishParams {
+                        game_room_id,
+                        result: GameRoomStatus::Win { addr: USER3.into() },
+                    },
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_err(err, ContractError::NotAContestant {});
+        assert_eq!(locked(&app, USER2), Uint128::new(750000000));
+    }
+
+    #[test]
+    fn test_query_unknown_game_room() {
+        let app = get_app();
+
+        // a missing room is a query error, not a panic of the contract
+        let err = app
+            .app
+            .wrap()
+            .query_wasm_smart::<GameRoomsState>(
+                app.enigma_addr.clone(),
+                &msg::QueryMsg::GetGameRoomState { game_room_id: 7 },
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+
     #[test]
     fn test_finish_game_room_draw() {
         let mut app = get_app();
@@ -377,9 +611,9 @@ mod tests {
         increase_allowance(&mut app, USER2);
         deposit(&mut app, USER2);
 
-        let game_room_key = create_gr(&mut app);
+        let game_room_id = create_gr(&mut app);
 
-        let _ = finish_gr(&mut app, game_room_key.clone(), GameRoomStatus::Draw {});
+        let _ = finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {});
         let con_1_bal: Option<Uint128> = app
             .app
             .wrap()
@@ -402,7 +636,7 @@ mod tests {
             .wrap()
             .query_wasm_smart(
                 app.enigma_addr.clone(),
-                &msg::QueryMsg::GetGameRoomState { game_room_key },
+                &msg::QueryMsg::GetGameRoomState { game_room_id },
             )
             .unwrap();
         assert_eq!(con_1_bal.unwrap(), Uint128::new(1000000000));
@@ -420,27 +654,45 @@ mod tests {
         increase_allowance(&mut app, USER2);
         deposit(&mut app, USER2);
 
-        let game_room_key = create_gr(&mut app);
+        let game_room_id = create_gr(&mut app);
 
         let _ = finish_gr(
             &mut app,
-            game_room_key.clone(),
+            game_room_id,
             GameRoomStatus::Win { addr: USER1.into() },
         );
-        let admin_bal: Option<Uint128> = app
+        let collected_fees: Uint128 = app
             .app
             .wrap()
-            .query_wasm_smart(
+            .query_wasm_smart(app.enigma_addr.clone(), &msg::QueryMsg::GetCollectedFees {})
+            .unwrap();
+
+        assert_eq!(Uint128::new(2_00_000_000), collected_fees);
+
+        // the treasurer can't take more than the ledger holds
+        let err = app
+            .app
+            .execute_contract(
+                Addr::unchecked(ENIGMA_ADMIN),
                 app.enigma_addr.clone(),
-                &msg::QueryMsg::GetUserBalance {
-                    user: ENIGMA_ADMIN.into(),
+                &crate::msg::ExecuteMsg::CollectFees {
+                    collect_fees_params: CollectFeesParams {
+                        amount: collected_fees + Uint128::one(),
+                        receiver: USER3.into(),
+                    },
                 },
+                &[],
             )
-            .unwrap();
-
-        assert_eq!(Uint128::new(2_00_000_000), admin_bal.unwrap());
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap().to_string(),
+            ContractError::InsufficientFees {
+                available: collected_fees
+            }
+            .to_string()
+        );
 
-        collect_fees(&mut app, USER3.into(), admin_bal.unwrap());
+        collect_fees(&mut app, USER3.into(), collected_fees);
 
         let edt_balance: BalanceResponse = app
             .app
@@ -454,6 +706,21 @@ mod tests {
             .unwrap();
         assert_eq!(Uint128::new(2_00_000_000), edt_balance.balance);
 
+        let ledger: FeeLedger = app
+            .app
+            .wrap()
+            .query_wasm_smart(app.enigma_addr.clone(), &msg::QueryMsg::GetFeeLedger {})
+            .unwrap();
+        assert_eq!(
+            ledger,
+            FeeLedger {
+                accrued: Uint128::new(200_000_000),
+                collected: Uint128::new(200_000_000),
+            }
+        );
+        assert_eq!(ledger.available(), Uint128::zero());
+
+        // the fees never touch the balance of the admin
         let admin_bal: Option<Uint128> = app
             .app
             .wrap()
@@ -464,7 +731,4316 @@ mod tests {
                 },
             )
             .unwrap();
-
         assert_eq!(Uint128::zero(), admin_bal.unwrap());
     }
+
+    mod migration {
+        use super::*;
+
+        use contract::{instantiate, migrate, migrate::GameRoomsStateV1};
+        use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+        use cw2::{get_contract_version, set_contract_version};
+        use cw_storage_plus::Map;
+        use msg::{MigrateMsg, Role};
+        use state::{
+            game_rooms_state, Balance, FeeDestination, FeeRecipient, BALANCES, FEE_ACCRUED,
+            FEE_CONFIG, FEE_LEDGER, FEE_RECIPIENTS, PLAYER_ROOMS, ROLES,
+        };
+
+        const CONTRACT_NAME: &str = "crates.io:enigmaduel";
+
+        #[test]
+        fn test_migrate_keeps_state() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let game_room_id = create_gr(&mut app);
+
+            app.app
+                .migrate_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &MigrateMsg::default(),
+                    app.enigma_code_id,
+                )
+                .unwrap();
+
+            let con_1_bal: Option<Uint128> = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &msg::QueryMsg::GetUserBalance { user: USER1.into() },
+                )
+                .unwrap();
+            let con_1_locked: Option<Uint128> = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &msg::QueryMsg::GetUserLockedBalance { user: USER1.into() },
+                )
+                .unwrap();
+            let gr_state: Option<GameRoomsState> = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &msg::QueryMsg::GetGameRoomState { game_room_id },
+                )
+                .unwrap();
+
+            assert_eq!(con_1_bal.unwrap(), Uint128::new(250000000));
+            assert_eq!(con_1_locked.unwrap(), Uint128::new(750000000));
+            assert_eq!(gr_state.unwrap().status, GameRoomStatus::Started {});
+
+            // the migrated contract must still be able to settle the room
+            finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {});
+        }
+
+        #[test]
+        fn test_migrate_from_older_version() {
+            let mut deps = mock_dependencies();
+            instantiate(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(DEPLOYER, &[]),
+                InstantiateMsg {
+                    fee: Uint128::new(100000000),
+                    admin: ENIGMA_ADMIN.into(),
+                    enigma_token_duel: "edt".into(),
+                    room_timeout: None,
+                },
+            )
+            .unwrap();
+
+            // storage written by an older release
+            let balance = Balance {
+                total: Uint128::new(250000000),
+                locked: Uint128::new(750000000),
+            };
+            let room = GameRoomsStateV1 {
+                contestant1: USER1.into(),
+                contestant2: USER2.into(),
+                prize_pool: Uint128::new(1500000000),
+                status: GameRoomStatus::Started {},
+            };
+            BALANCES
+                .save(deps.as_mut().storage, &Addr::unchecked(USER1), &balance)
+                .unwrap();
+            Map::<String, GameRoomsStateV1>::new("game_rooms")
+                .save(deps.as_mut().storage, "room".into(), &room)
+                .unwrap();
+            // the single flat fee of the releases before 0.22.0
+            FEE_CONFIG.remove(deps.as_mut().storage);
+            cw_storage_plus::Item::<Uint128>::new("fee")
+                .save(deps.as_mut().storage, &Uint128::new(100000000))
+                .unwrap();
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+            let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+            assert_eq!(res.attributes[1].value, "0.0.1");
+
+            let version = get_contract_version(deps.as_ref().storage).unwrap();
+            assert_eq!(version.contract, CONTRACT_NAME);
+            assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+            assert_eq!(
+                FEE_CONFIG.load(deps.as_ref().storage).unwrap(),
+                state::FeeConfig::flat(Uint128::new(100000000))
+            );
+            // the migrated room is found by both of its contestants
+            for user in [USER1, USER2] {
+                assert!(PLAYER_ROOMS.has(deps.as_ref().storage, (user, 1)));
+            }
+
+            assert_eq!(
+                BALANCES
+                    .load(deps.as_ref().storage, &Addr::unchecked(USER1))
+                    .unwrap(),
+                balance
+            );
+            let block = mock_env().block;
+            assert_eq!(
+                game_rooms_state().load(deps.as_ref().storage, 1).unwrap(),
+                GameRoomsState {
+                    contestant1: room.contestant1,
+                    contestant2: room.contestant2,
+                    prize_pool: room.prize_pool,
+                    status: room.status,
+                    created_at: block.time,
+                    created_height: block.height,
+                    deadline: block.time.plus_seconds(contract::DEFAULT_ROOM_TIMEOUT),
+                    secret_hash: None,
+                    extra_contestants: vec![],
+                    payout_table: vec![],
+                    series: None,
+                    stakes: vec![],
+                    payout_policy: Default::default(),
+                    teams: vec![],
+                    fee_config: None,
+                }
+            );
+        }
+
+        #[test]
+        fn test_migrate_grants_admin_roles() {
+            let mut deps = mock_dependencies();
+            instantiate(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(DEPLOYER, &[]),
+                InstantiateMsg {
+                    fee: Uint128::new(100000000),
+                    admin: ENIGMA_ADMIN.into(),
+                    enigma_token_duel: "edt".into(),
+                    room_timeout: None,
+                },
+            )
+            .unwrap();
+
+            // 0.1.0 had no role registry
+            for role in Role::all() {
+                ROLES.remove(
+                    deps.as_mut().storage,
+                    (role.key(), &Addr::unchecked(ENIGMA_ADMIN)),
+                );
+            }
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+            migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+
+            for role in Role::all() {
+                assert!(crate::roles::has_role(
+                    deps.as_ref().storage,
+                    &role,
+                    &Addr::unchecked(ENIGMA_ADMIN)
+                ));
+            }
+        }
+
+        #[test]
+        fn test_migrate_moves_admin_fees() {
+            let mut deps = mock_dependencies();
+            instantiate(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(DEPLOYER, &[]),
+                InstantiateMsg {
+                    fee: Uint128::new(100000000),
+                    admin: ENIGMA_ADMIN.into(),
+                    enigma_token_duel: "edt".into(),
+                    room_timeout: None,
+                },
+            )
+            .unwrap();
+
+            // 0.22.0 credited the fees to the admin balance, on top of a 300 deposit of the admin
+            let admin = Addr::unchecked(ENIGMA_ADMIN);
+            BALANCES
+                .save(
+                    deps.as_mut().storage,
+                    &admin,
+                    &Balance {
+                        total: Uint128::new(500000000),
+                        locked: Uint128::new(750000000),
+                    },
+                )
+                .unwrap();
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.22.0").unwrap();
+
+            // the fees can't be more than the admin holds
+            let err = migrate(
+                deps.as_mut(),
+                mock_env(),
+                MigrateMsg {
+                    admin_fees: Some(Uint128::new(600000000)),
+                },
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::InsufficientBalance(_)));
+            // the chain reverts a failed migration, the mock storage doesn't
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.22.0").unwrap();
+
+            migrate(
+                deps.as_mut(),
+                mock_env(),
+                MigrateMsg {
+                    admin_fees: Some(Uint128::new(200000000)),
+                },
+            )
+            .unwrap();
+
+            assert_eq!(
+                FEE_LEDGER.load(deps.as_ref().storage).unwrap().available(),
+                Uint128::new(200000000)
+            );
+            // the deposit stays with the admin, as does the stake of an ongoing game
+            assert_eq!(
+                BALANCES.load(deps.as_ref().storage, &admin).unwrap(),
+                Balance {
+                    total: Uint128::new(300000000),
+                    locked: Uint128::new(750000000),
+                }
+            );
+        }
+
+        #[test]
+        fn test_migrate_keys_fee_accrued_by_destination() {
+            let mut deps = mock_dependencies();
+            instantiate(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(DEPLOYER, &[]),
+                InstantiateMsg {
+                    fee: Uint128::new(100000000),
+                    admin: ENIGMA_ADMIN.into(),
+                    enigma_token_duel: "edt".into(),
+                    room_timeout: None,
+                },
+            )
+            .unwrap();
+
+            // 0.31.0 kept the accrued fees by recipient name, "old" was removed since
+            let storage = deps.as_mut().storage;
+            FEE_RECIPIENTS
+                .save(
+                    storage,
+                    &vec![
+                        FeeRecipient {
+                            name: "treasury".into(),
+                            destination: FeeDestination::Balance { addr: USER4.into() },
+                            weight: 3,
+                        },
+                        FeeRecipient {
+                            name: "burn".into(),
+                            destination: FeeDestination::Burn {},
+                            weight: 1,
+                        },
+                    ],
+                )
+                .unwrap();
+            let fee_accrued_v1: Map<&str, Uint128> = Map::new("fee_accrued");
+            for (name, accrued) in [("treasury", 300000000), ("burn", 100000000), ("old", 5)] {
+                fee_accrued_v1
+                    .save(storage, name, &Uint128::new(accrued))
+                    .unwrap();
+            }
+            set_contract_version(storage, CONTRACT_NAME, "0.31.0").unwrap();
+
+            migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+
+            let storage = deps.as_ref().storage;
+            assert_eq!(
+                FEE_ACCRUED.load(storage, USER4).unwrap(),
+                Uint128::new(300000000)
+            );
+            assert_eq!(
+                FEE_ACCRUED.load(storage, "burn").unwrap(),
+                Uint128::new(100000000)
+            );
+            assert!(fee_accrued_v1.is_empty(storage));
+        }
+
+        #[test]
+        fn test_migrate_without_admin_fees_keeps_admin_balance() {
+            let mut deps = mock_dependencies();
+            instantiate(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(DEPLOYER, &[]),
+                InstantiateMsg {
+                    fee: Uint128::new(100000000),
+                    admin: ENIGMA_ADMIN.into(),
+                    enigma_token_duel: "edt".into(),
+                    room_timeout: None,
+                },
+            )
+            .unwrap();
+
+            let admin = Addr::unchecked(ENIGMA_ADMIN);
+            let balance = Balance {
+                total: Uint128::new(300000000),
+                locked: Uint128::zero(),
+            };
+            BALANCES
+                .save(deps.as_mut().storage, &admin, &balance)
+                .unwrap();
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.22.0").unwrap();
+
+            migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+
+            assert_eq!(
+                BALANCES.load(deps.as_ref().storage, &admin).unwrap(),
+                balance
+            );
+            assert_eq!(
+                FEE_LEDGER
+                    .may_load(deps.as_ref().storage)
+                    .unwrap()
+                    .unwrap_or_default()
+                    .available(),
+                Uint128::zero()
+            );
+        }
+
+        #[test]
+        fn test_migrate_rejects_downgrade_and_foreign_contract() {
+            let mut deps = mock_dependencies();
+
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+            migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+
+            set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.0.1").unwrap();
+            migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+        }
+    }
+
+    mod admin {
+        use super::*;
+
+        use msg::{ExecuteMsg, GetAdminResp, GetUserRolesResp, QueryMsg, Role};
+
+        fn query_admin(app: &MockApp) -> GetAdminResp {
+            app.app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetAdmin {})
+                .unwrap()
+        }
+
+        fn user_roles(app: &MockApp, user: &str) -> Vec<Role> {
+            let resp: GetUserRolesResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetUserRoles { user: user.into() },
+                )
+                .unwrap();
+            resp.roles
+        }
+
+        #[test]
+        fn test_propose_and_accept_admin() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let game_room_id = create_gr(&mut app);
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER1.into() },
+            );
+
+            // only the admin can propose
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER1),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::ProposeAdmin {
+                        new_admin: USER1.into(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::ProposeAdmin {
+                        new_admin: USER3.into(),
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                query_admin(&app),
+                GetAdminResp {
+                    admin: Some(ENIGMA_ADMIN.into()),
+                    pending_admin: Some(USER3.into()),
+                }
+            );
+
+            // only the pending admin can accept
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER2),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::AcceptAdmin {},
+                    &[],
+                )
+                .unwrap_err();
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER3),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::AcceptAdmin {},
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                query_admin(&app),
+                GetAdminResp {
+                    admin: Some(USER3.into()),
+                    pending_admin: None,
+                }
+            );
+            // the roles go along with the admin
+            assert_eq!(user_roles(&app, USER3), Role::all());
+            assert!(user_roles(&app, ENIGMA_ADMIN).is_empty());
+
+            // the collected fees stay in the ledger, apart from both admins
+            assert_eq!(query_balance(&app, ENIGMA_ADMIN), Uint128::zero());
+            assert_eq!(query_balance(&app, USER3), Uint128::zero());
+            let collected_fees: Uint128 = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetCollectedFees {})
+                .unwrap();
+            assert_eq!(collected_fees, Uint128::new(200_000_000));
+
+            // the old admin lost its rights
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::ProposeAdmin {
+                        new_admin: ENIGMA_ADMIN.into(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+        }
+
+        #[test]
+        fn test_accept_without_proposal() {
+            let mut app = get_app();
+
+            let err = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(USER3),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::AcceptAdmin {},
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                ContractError::NoPendingAdmin {}.to_string()
+            );
+        }
+
+        #[test]
+        fn test_renounce_admin() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::ProposeAdmin {
+                        new_admin: USER3.into(),
+                    },
+                    &[],
+                )
+                .unwrap();
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::RenounceAdmin {},
+                    &[],
+                )
+                .unwrap();
+
+            assert_eq!(
+                query_admin(&app),
+                GetAdminResp {
+                    admin: None,
+                    pending_admin: None,
+                }
+            );
+            // the ex-admin keeps none of the roles and the proposed one never got them
+            assert!(user_roles(&app, ENIGMA_ADMIN).is_empty());
+            assert!(user_roles(&app, USER3).is_empty());
+
+            // nobody can manage the roles anymore
+            let err = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::GrantRole {
+                        role: Role::Operator,
+                        addr: USER3.into(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                ContractError::Unauthorized {}.to_string()
+            );
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER3),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::AcceptAdmin {},
+                    &[],
+                )
+                .unwrap_err();
+        }
+    }
+
+    mod roles {
+        use super::*;
+
+        use msg::{ExecuteMsg, GetUserRolesResp, ListRoleMembersResp, QueryMsg, Role};
+
+        pub const OPERATOR: &str = "addr6666";
+        pub const TREASURER: &str = "addr7777";
+
+        fn grant(app: &mut MockApp, role: Role, addr: &str) {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::GrantRole {
+                        role,
+                        addr: addr.into(),
+                    },
+                    &[],
+                )
+                .unwrap();
+        }
+
+        fn revoke(app: &mut MockApp, role: Role, addr: &str) {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::RevokeRole {
+                        role,
+                        addr: addr.into(),
+                    },
+                    &[],
+                )
+                .unwrap();
+        }
+
+        #[test]
+        fn test_grant_and_list_roles() {
+            let mut app = get_app();
+
+            grant(&mut app, Role::Operator, OPERATOR);
+            grant(&mut app, Role::Treasurer, TREASURER);
+
+            let operators: ListRoleMembersResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListRoleMembers {
+                        role: Role::Operator,
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                operators.members,
+                vec![ENIGMA_ADMIN.to_string(), OPERATOR.to_string()]
+            );
+
+            let operators: ListRoleMembersResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListRoleMembers {
+                        role: Role::Operator,
+                        start_after: Some(ENIGMA_ADMIN.into()),
+                        limit: Some(1),
+                    },
+                )
+                .unwrap();
+            assert_eq!(operators.members, vec![OPERATOR.to_string()]);
+
+            let roles: GetUserRolesResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetUserRoles {
+                        user: TREASURER.into(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(roles.roles, vec![Role::Treasurer]);
+
+            revoke(&mut app, Role::Treasurer, TREASURER);
+            let roles: GetUserRolesResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetUserRoles {
+                        user: TREASURER.into(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(roles.roles, vec![]);
+        }
+
+        #[test]
+        fn test_only_admin_manages_roles() {
+            let mut app = get_app();
+
+            grant(&mut app, Role::Operator, OPERATOR);
+            app.app
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::GrantRole {
+                        role: Role::Treasurer,
+                        addr: OPERATOR.into(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+        }
+
+        #[test]
+        fn test_handlers_check_roles() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            grant(&mut app, Role::Operator, OPERATOR);
+            grant(&mut app, Role::Treasurer, TREASURER);
+            revoke(&mut app, Role::Operator, ENIGMA_ADMIN);
+            revoke(&mut app, Role::Treasurer, ENIGMA_ADMIN);
+
+            // the admin can't run the rooms without the operator role
+            let err = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateGameRoom {
+                        game_room_init_params: GameRoomIntiParams {
+                            contestant1: USER1.into(),
+                            contestant2: USER2.into(),
+                            prize_pool: Uint128::new(1500000000),
+                            status: GameRoomStatus::Started {},
+                            consents: None,
+                            payout_policy: None,
+                            fee_config: None,
+                        },
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                ContractError::MissingRole {
+                    role: Role::Operator
+                }
+                .to_string()
+            );
+
+            let res = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateGameRoom {
+                        game_room_init_params: GameRoomIntiParams {
+                            contestant1: USER1.into(),
+                            contestant2: USER2.into(),
+                            prize_pool: Uint128::new(1500000000),
+                            status: GameRoomStatus::Started {},
+                            consents: None,
+                            payout_policy: None,
+                            fee_config: None,
+                        },
+                    },
+                    &[],
+                )
+                .unwrap();
+            let game_room_id = res.events[1].attributes[2].value.parse().unwrap();
+            join_gr(&mut app, USER1, game_room_id);
+            join_gr(&mut app, USER2, game_room_id);
+
+            // the operator finishes the room but can't touch the fees
+            app.app
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::FinishGameRoom {
+                        game_room_finish_params: GameRoomFinishParams {
+                            game_room_id,
+                            result: GameRoomStatus::Win { addr: USER1.into() },
+                        },
+                    },
+                    &[],
+                )
+                .unwrap();
+            let collect_msg = ExecuteMsg::CollectFees {
+                collect_fees_params: CollectFeesParams {
+                    amount: Uint128::new(200_000_000),
+                    receiver: USER3.into(),
+                },
+            };
+            app.app
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    app.enigma_addr.clone(),
+                    &collect_msg,
+                    &[],
+                )
+                .unwrap_err();
+            app.app
+                .execute_contract(
+                    Addr::unchecked(TREASURER),
+                    app.enigma_addr.clone(),
+                    &collect_msg,
+                    &[],
+                )
+                .unwrap();
+
+            let edt_balance: BalanceResponse = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.edt_addr,
+                    &test_edt::msg::QueryMsg::Balance {
+                        address: USER3.into(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(Uint128::new(200_000_000), edt_balance.balance);
+        }
+    }
+
+    mod pause {
+        use super::*;
+
+        use msg::{ExecuteMsg, GetPauseStateResp, QueryMsg};
+        use state::PauseState;
+
+        fn set_pause_state(app: &mut MockApp, sender: &str, pause_state: PauseState) {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(sender),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::SetPauseState { pause_state },
+                    &[],
+                )
+                .unwrap();
+        }
+
+        fn query_pause_state(app: &MockApp) -> GetPauseStateResp {
+            app.app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetPauseState {})
+                .unwrap()
+        }
+
+        fn paused_err(operation: &str) -> String {
+            ContractError::OperationPaused {
+                operation: operation.into(),
+            }
+            .to_string()
+        }
+
+        #[test]
+        fn test_only_pauser_pauses() {
+            let mut app = get_app();
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER1),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::WithdrawOnly {},
+                    &[],
+                )
+                .unwrap_err();
+
+            assert_eq!(
+                query_pause_state(&app),
+                GetPauseStateResp {
+                    pause_state: PauseState::default(),
+                    withdraw_only: false,
+                }
+            );
+        }
+
+        #[test]
+        fn test_pause_deposits() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            set_pause_state(
+                &mut app,
+                ENIGMA_ADMIN,
+                PauseState {
+                    deposits: true,
+                    ..PauseState::default()
+                },
+            );
+
+            let err = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(USER1),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::UpdateBalance {
+                        update_mode: msg::UpdateBalanceMode::Deposit {
+                            user: Some(USER1.into()),
+                            amount: Uint128::new(1000000000),
+                        },
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                paused_err("deposits")
+            );
+
+            set_pause_state(&mut app, ENIGMA_ADMIN, PauseState::default());
+            deposit(&mut app, USER1);
+        }
+
+        #[test]
+        fn test_withdraw_only() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let game_room_id = create_gr(&mut app);
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::WithdrawOnly {},
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                query_pause_state(&app),
+                GetPauseStateResp {
+                    pause_state: PauseState::withdraw_only(),
+                    withdraw_only: true,
+                }
+            );
+
+            // rooms can't be created nor settled
+            let err = try_finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {}).unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                paused_err("room_settlement")
+            );
+
+            // the unlocked funds can still leave
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER1),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::UpdateBalance {
+                        update_mode: msg::UpdateBalanceMode::Withdraw {
+                            user: Some(USER1.into()),
+                            amount: Uint128::new(250000000),
+                            receiver: USER1.into(),
+                        },
+                    },
+                    &[],
+                )
+                .unwrap();
+
+            // resuming lets the room settle
+            set_pause_state(&mut app, ENIGMA_ADMIN, PauseState::default());
+            finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {});
+
+            let con_1_bal: Uint128 = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetUserBalance { user: USER1.into() },
+                )
+                .unwrap();
+            assert_eq!(con_1_bal, Uint128::new(750000000));
+        }
+    }
+
+    mod consent {
+        use super::*;
+
+        use crate::consent::consent_hash;
+        use cosmwasm_std::{Binary, Timestamp};
+        use cw_multi_test::AppResponse;
+        use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+        use msg::{ConsentPayload, ExecuteMsg, PayoutPolicy, RoomConsents};
+        use state::FeeConfig;
+
+        const STAKE: u128 = 750000000;
+        const ROOM_KEY: &str = "match-1";
+
+        fn signing_key(seed: u8) -> SigningKey {
+            SigningKey::from_bytes(&[seed; 32].into()).unwrap()
+        }
+
+        fn register_key(app: &mut MockApp, user: &str, key: &SigningKey) {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(user),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::RegisterConsentKey {
+                        pubkey: Binary::from(key.verifying_key().to_encoded_point(true).as_bytes()),
+                    },
+                    &[],
+                )
+                .unwrap();
+        }
+
+        // the consent to a room of the two funded users with the default terms
+        fn sign(app: &MockApp, key: &SigningKey, room_key: &str, expires: Timestamp) -> Binary {
+            let hash = consent_hash(&ConsentPayload {
+                contract: app.enigma_addr.to_string(),
+                contestant1: USER1.into(),
+                contestant2: USER2.into(),
+                stake: Uint128::new(STAKE),
+                payout_policy: PayoutPolicy::default(),
+                fee_config: Some(FeeConfig::flat(Uint128::new(100000000))),
+                room_key: room_key.into(),
+                expires,
+            })
+            .unwrap();
+            let signature: Signature = key.sign_prehash(&hash).unwrap();
+            Binary::from(signature.to_bytes().as_slice())
+        }
+
+        fn create_gr_with_consents(
+            app: &mut MockApp,
+            consents: Option<RoomConsents>,
+            fee_config: Option<FeeConfig>,
+        ) -> anyhow::Result<AppResponse> {
+            app.app.execute_contract(
+                Addr::unchecked(ENIGMA_ADMIN),
+                app.enigma_addr.clone(),
+                &ExecuteMsg::CreateGameRoom {
+                    game_room_init_params: GameRoomIntiParams {
+                        contestant1: USER1.into(),
+                        contestant2: USER2.into(),
+                        prize_pool: Uint128::new(1500000000),
+                        status: GameRoomStatus::Started {},
+                        consents,
+                        payout_policy: None,
+                        fee_config,
+                    },
+                },
+                &[],
+            )
+        }
+
+        fn contract_err(err: anyhow::Error) -> String {
+            err.downcast::<ContractError>().unwrap().to_string()
+        }
+
+        #[test]
+        fn test_join_room() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let res = create_gr_with_consents(&mut app, None, None).unwrap();
+            let game_room_id = res.events[1].attributes[2].value.parse().unwrap();
+
+            // nothing is locked before the contestants agree
+            assert_eq!(
+                query_room(&app, game_room_id).status,
+                GameRoomStatus::Pending { joined: vec![] }
+            );
+            assert_eq!(locked(&app, USER1), Uint128::zero());
+
+            let err = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(USER3),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::JoinRoom {
+                        game_room_id,
+                        secret: None,
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                contract_err(err),
+                ContractError::NotAContestant {}.to_string()
+            );
+
+            join_gr(&mut app, USER1, game_room_id);
+            assert_eq!(
+                query_room(&app, game_room_id).status,
+                GameRoomStatus::Pending {
+                    joined: vec![USER1.into()]
+                }
+            );
+            assert_eq!(locked(&app, USER1), Uint128::zero());
+
+            // a pending room can't be settled
+            let err = try_finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {}).unwrap_err();
+            assert_eq!(
+                contract_err(err),
+                ContractError::GameRoomNotStarted {}.to_string()
+            );
+
+            join_gr(&mut app, USER2, game_room_id);
+            assert_eq!(
+                query_room(&app, game_room_id).status,
+                GameRoomStatus::Started {}
+            );
+            assert_eq!(locked(&app, USER1), Uint128::new(STAKE));
+            assert_eq!(locked(&app, USER2), Uint128::new(STAKE));
+        }
+
+        #[test]
+        fn test_signed_consents() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let (key_1, key_2) = (signing_key(1), signing_key(2));
+            register_key(&mut app, USER1, &key_1);
+            register_key(&mut app, USER2, &key_2);
+
+            let expires = app.app.block_info().time.plus_seconds(60);
+            let consents = RoomConsents {
+                room_key: ROOM_KEY.into(),
+                expires,
+                contestant1_signature: sign(&app, &key_1, ROOM_KEY, expires),
+                contestant2_signature: sign(&app, &key_2, ROOM_KEY, expires),
+            };
+
+            // a consent signed by the wrong key is rejected
+            let forged = RoomConsents {
+                contestant2_signature: sign(&app, &key_1, ROOM_KEY, expires),
+                ..consents.clone()
+            };
+            let err = create_gr_with_consents(&mut app, Some(forged), None).unwrap_err();
+            assert_eq!(
+                contract_err(err),
+                ContractError::InvalidConsentSignature { user: USER2.into() }.to_string()
+            );
+
+            // the consents don't carry over to a room with other terms
+            let err = create_gr_with_consents(
+                &mut app,
+                Some(consents.clone()),
+                Some(FeeConfig::flat(Uint128::new(50000000))),
+            )
+            .unwrap_err();
+            assert_eq!(
+                contract_err(err),
+                ContractError::InvalidConsentSignature { user: USER1.into() }.to_string()
+            );
+
+            // the signed consents start the room right away
+            let res = create_gr_with_consents(&mut app, Some(consents.clone()), None).unwrap();
+            let game_room_id = res.events[1].attributes[2].value.parse().unwrap();
+            assert_eq!(
+                query_room(&app, game_room_id).status,
+                GameRoomStatus::Started {}
+            );
+            assert_eq!(locked(&app, USER1), Uint128::new(STAKE));
+            assert_eq!(locked(&app, USER2), Uint128::new(STAKE));
+
+            // the same consents can't start another room
+            finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {});
+            let err = create_gr_with_consents(&mut app, Some(consents.clone()), None).unwrap_err();
+            assert_eq!(
+                contract_err(err),
+                ContractError::ConsentAlreadyUsed {}.to_string()
+            );
+
+            // nor can they be redirected to a room with another key
+            let redirected = RoomConsents {
+                room_key: "match-2".into(),
+                ..consents
+            };
+            let err = create_gr_with_consents(&mut app, Some(redirected), None).unwrap_err();
+            assert_eq!(
+                contract_err(err),
+                ContractError::InvalidConsentSignature { user: USER1.into() }.to_string()
+            );
+
+            // and a room key starts a single room even with fresh consents
+            let expires = expires.plus_seconds(1);
+            let fresh = RoomConsents {
+                room_key: ROOM_KEY.into(),
+                expires,
+                contestant1_signature: sign(&app, &key_1, ROOM_KEY, expires),
+                contestant2_signature: sign(&app, &key_2, ROOM_KEY, expires),
+            };
+            let err = create_gr_with_consents(&mut app, Some(fresh), None).unwrap_err();
+            assert_eq!(
+                contract_err(err),
+                ContractError::ConsentAlreadyUsed {}.to_string()
+            );
+        }
+
+        #[test]
+        fn test_expired_consents() {
+            let mut app = get_app();
+
+            let (key_1, key_2) = (signing_key(1), signing_key(2));
+            register_key(&mut app, USER1, &key_1);
+            register_key(&mut app, USER2, &key_2);
+
+            let expires = app.app.block_info().time;
+            let consents = RoomConsents {
+                room_key: ROOM_KEY.into(),
+                expires,
+                contestant1_signature: sign(&app, &key_1, ROOM_KEY, expires),
+                contestant2_signature: sign(&app, &key_2, ROOM_KEY, expires),
+            };
+            let err = create_gr_with_consents(&mut app, Some(consents), None).unwrap_err();
+            assert_eq!(
+                contract_err(err),
+                ContractError::ConsentExpired {}.to_string()
+            );
+        }
+    }
+
+    mod timeout {
+        use super::*;
+
+        use contract::DEFAULT_ROOM_TIMEOUT;
+        use msg::{ExecuteMsg, GetRoomTimeoutResp, QueryMsg};
+
+        fn expire_gr(app: &mut MockApp, game_room_id: u64) -> anyhow::Result<()> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER3),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::ExpireGameRoom { game_room_id },
+                    &[],
+                )
+                .map(|_| ())
+        }
+
+        #[test]
+        fn test_expire_game_room() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let game_room_id = create_gr(&mut app);
+            let room = query_room(&app, game_room_id);
+            let block = app.app.block_info();
+            assert_eq!(room.created_at, block.time);
+            assert_eq!(room.created_height, block.height);
+            assert_eq!(room.deadline, block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT));
+
+            let err = expire_gr(&mut app, game_room_id).unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                ContractError::GameRoomDeadlineNotReached {}.to_string()
+            );
+
+            // the game server never settled the room
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT);
+                block.height += 600;
+            });
+            expire_gr(&mut app, game_room_id).unwrap();
+
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1000000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1000000000));
+            assert_eq!(
+                query_room(&app, game_room_id).status,
+                GameRoomStatus::Expired {}
+            );
+
+            // an expired room can neither be settled nor expired again
+            try_finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER1.into() },
+            )
+            .unwrap_err();
+            expire_gr(&mut app, game_room_id).unwrap_err();
+        }
+
+        #[test]
+        fn test_update_room_timeout() {
+            let mut app = get_app();
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER1),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::UpdateRoomTimeout { room_timeout: 60 },
+                    &[],
+                )
+                .unwrap_err();
+            // a room could be expired before the game servers even see it
+            assert_err(
+                app.app
+                    .execute_contract(
+                        Addr::unchecked(ENIGMA_ADMIN),
+                        app.enigma_addr.clone(),
+                        &ExecuteMsg::UpdateRoomTimeout { room_timeout: 0 },
+                        &[],
+                    )
+                    .unwrap_err(),
+                ContractError::InvalidRoomTimeout { min: 60 },
+            );
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::UpdateRoomTimeout { room_timeout: 60 },
+                    &[],
+                )
+                .unwrap();
+
+            let timeout: GetRoomTimeoutResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetRoomTimeout {})
+                .unwrap();
+            assert_eq!(timeout.room_timeout, 60);
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let game_room_id = create_gr(&mut app);
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(60);
+            });
+            expire_gr(&mut app, game_room_id).unwrap();
+        }
+    }
+
+    mod room_ids {
+        use super::*;
+
+        use cosmwasm_std::from_json;
+        use msg::{
+            CreateGameRoomResp, ExecuteMsg, GameRoomStatusKind, ListGameRoomsResp, QueryMsg,
+        };
+
+        fn create_room(app: &mut MockApp, contestant1: &str, contestant2: &str) -> u64 {
+            let res = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateGameRoom {
+                        game_room_init_params: GameRoomIntiParams {
+                            contestant1: contestant1.into(),
+                            contestant2: contestant2.into(),
+                            prize_pool: Uint128::new(500000000),
+                            status: GameRoomStatus::Started {},
+                            consents: None,
+                            payout_policy: None,
+                            fee_config: None,
+                        },
+                    },
+                    &[],
+                )
+                .unwrap();
+            from_json::<CreateGameRoomResp>(res.data.unwrap())
+                .unwrap()
+                .0
+        }
+
+        fn rooms_by_pair(
+            app: &MockApp,
+            contestant1: &str,
+            contestant2: &str,
+            start_after: Option<u64>,
+        ) -> Vec<u64> {
+            let resp: ListGameRoomsResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListRoomsByPair {
+                        contestant1: contestant1.into(),
+                        contestant2: contestant2.into(),
+                        start_after,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            resp.rooms
+                .into_iter()
+                .map(|room| room.game_room_id)
+                .collect()
+        }
+
+        #[test]
+        fn test_concurrent_rooms_per_pair() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            // both orders of the same pair play at the same time
+            let first = create_room(&mut app, USER1, USER2);
+            let second = create_room(&mut app, USER2, USER1);
+            let other = create_room(&mut app, USER1, USER3);
+            assert_eq!((first, second, other), (1, 2, 3));
+
+            for game_room_id in [first, second] {
+                join_gr(&mut app, USER1, game_room_id);
+                join_gr(&mut app, USER2, game_room_id);
+            }
+            let locked: Uint128 = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetUserLockedBalance { user: USER1.into() },
+                )
+                .unwrap();
+            assert_eq!(locked, Uint128::new(500000000));
+
+            finish_gr(&mut app, first, GameRoomStatus::Draw {});
+            finish_gr(&mut app, second, GameRoomStatus::Draw {});
+
+            assert_eq!(rooms_by_pair(&app, USER1, USER2, None), vec![1, 2]);
+            assert_eq!(rooms_by_pair(&app, USER2, USER1, None), vec![1, 2]);
+            assert_eq!(rooms_by_pair(&app, USER2, USER1, Some(1)), vec![2]);
+            assert_eq!(rooms_by_pair(&app, USER3, USER1, None), vec![3]);
+        }
+
+        fn list_rooms(app: &MockApp, query: QueryMsg) -> Vec<u64> {
+            let resp: ListGameRoomsResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &query)
+                .unwrap();
+            resp.rooms
+                .into_iter()
+                .map(|room| room.game_room_id)
+                .collect()
+        }
+
+        #[test]
+        fn test_list_rooms_by_status_and_player() {
+            let mut app = get_app();
+
+            for user in [USER1, USER2] {
+                increase_allowance(&mut app, user);
+                deposit(&mut app, user);
+            }
+
+            let drawn = create_room(&mut app, USER1, USER2);
+            let started = create_room(&mut app, USER2, USER1);
+            let pending = create_room(&mut app, USER3, USER1);
+
+            for game_room_id in [drawn, started] {
+                join_gr(&mut app, USER1, game_room_id);
+                join_gr(&mut app, USER2, game_room_id);
+            }
+            finish_gr(&mut app, drawn, GameRoomStatus::Draw {});
+
+            let list_game_rooms = |start_after, limit, status| QueryMsg::ListGameRooms {
+                start_after,
+                limit,
+                status,
+            };
+            assert_eq!(
+                list_rooms(&app, list_game_rooms(None, None, None)),
+                vec![drawn, started, pending]
+            );
+            assert_eq!(
+                list_rooms(&app, list_game_rooms(Some(drawn), Some(1), None)),
+                vec![started]
+            );
+            assert_eq!(
+                list_rooms(
+                    &app,
+                    list_game_rooms(None, None, Some(GameRoomStatusKind::Draw))
+                ),
+                vec![drawn]
+            );
+            assert_eq!(
+                list_rooms(
+                    &app,
+                    list_game_rooms(None, None, Some(GameRoomStatusKind::Pending))
+                ),
+                vec![pending]
+            );
+            assert!(list_rooms(
+                &app,
+                list_game_rooms(None, None, Some(GameRoomStatusKind::Expired))
+            )
+            .is_empty());
+
+            let by_player = |player: &str, start_after, limit| QueryMsg::ListRoomsByPlayer {
+                player: player.into(),
+                start_after,
+                limit,
+            };
+            assert_eq!(
+                list_rooms(&app, by_player(USER1, None, None)),
+                vec![drawn, started, pending]
+            );
+            assert_eq!(
+                list_rooms(&app, by_player(USER1, Some(drawn), Some(1))),
+                vec![started]
+            );
+            assert_eq!(
+                list_rooms(&app, by_player(USER2, None, None)),
+                vec![drawn, started]
+            );
+            assert_eq!(
+                list_rooms(&app, by_player(USER3, None, None)),
+                vec![pending]
+            );
+
+            assert_eq!(
+                list_rooms(
+                    &app,
+                    QueryMsg::ListActiveRooms {
+                        start_after: None,
+                        limit: None
+                    }
+                ),
+                vec![started, pending]
+            );
+            assert_eq!(
+                list_rooms(
+                    &app,
+                    QueryMsg::ListActiveRooms {
+                        start_after: None,
+                        limit: Some(1)
+                    }
+                ),
+                vec![started]
+            );
+        }
+    }
+
+    mod stats {
+        use super::*;
+
+        use cosmwasm_std::Int128;
+        use msg::{ListPlayerHistoryResp, MatchOutcome, QueryMsg};
+        use state::PlayerStats;
+
+        fn query_history(
+            app: &MockApp,
+            player: &str,
+            start_after: Option<u64>,
+        ) -> Vec<(u64, MatchOutcome)> {
+            let resp: ListPlayerHistoryResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListPlayerHistory {
+                        player: player.into(),
+                        start_after,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            resp.matches
+                .into_iter()
+                .map(|m| (m.game_room_id, m.record.outcome))
+                .collect()
+        }
+
+        #[test]
+        fn test_player_stats_and_history() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            assert_eq!(query_stats(&app, USER1), PlayerStats::default());
+
+            let drawn = create_gr(&mut app);
+            finish_gr(&mut app, drawn, GameRoomStatus::Draw {});
+            let won = create_gr(&mut app);
+            finish_gr(&mut app, won, GameRoomStatus::Win { addr: USER2.into() });
+
+            assert_eq!(
+                query_stats(&app, USER1),
+                PlayerStats {
+                    games_played: 2,
+                    wins: 0,
+                    losses: 1,
+                    draws: 1,
+                    total_wagered: Uint128::new(1500000000),
+                    net_profit: Int128::new(-750000000),
+                    fees_paid: Uint128::new(100000000),
+                }
+            );
+            assert_eq!(
+                query_stats(&app, USER2),
+                PlayerStats {
+                    games_played: 2,
+                    wins: 1,
+                    losses: 0,
+                    draws: 1,
+                    total_wagered: Uint128::new(1500000000),
+                    net_profit: Int128::new(550000000),
+                    fees_paid: Uint128::new(100000000),
+                }
+            );
+
+            assert_eq!(
+                query_history(&app, USER1, None),
+                vec![(won, MatchOutcome::Loss), (drawn, MatchOutcome::Draw)]
+            );
+            assert_eq!(
+                query_history(&app, USER2, Some(won)),
+                vec![(drawn, MatchOutcome::Draw)]
+            );
+            assert!(query_history(&app, USER3, None).is_empty());
+        }
+
+        #[test]
+        fn test_net_profit_matches_balance_change() {
+            let mut app = funded_app();
+
+            let game_room_id = create_gr(&mut app);
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER1.into() },
+            );
+
+            for user in [USER1, USER2] {
+                let balance = Int128::try_from(query_balance(&app, user)).unwrap();
+                assert_eq!(
+                    query_stats(&app, user).net_profit,
+                    balance - Int128::new(1000000000)
+                );
+            }
+        }
+    }
+
+    mod ratings {
+        use super::*;
+
+        use crate::ratings::{expected_score, rate_duel};
+        use cosmwasm_std::Decimal;
+        use msg::{ExecuteMsg, GetRatingResp, ListRatingHistoryResp, QueryMsg};
+
+        fn query_rating(app: &MockApp, player: &str) -> u32 {
+            let resp: GetRatingResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetRating {
+                        player: player.into(),
+                    },
+                )
+                .unwrap();
+            resp.rating
+        }
+
+        fn update_k_factor(app: &mut MockApp, sender: &str, k_factor: u32) -> anyhow::Result<()> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(sender),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::UpdateEloKFactor { k_factor },
+                    &[],
+                )
+                .map(|_| ())
+        }
+
+        #[test]
+        fn test_elo_math() {
+            assert_eq!(expected_score(1200, 1200), Decimal::percent(50));
+            assert_eq!(
+                expected_score(1600, 1200) + expected_score(1200, 1600),
+                Decimal::one()
+            );
+            // the gap is capped at 400 points
+            assert_eq!(expected_score(2400, 1200), expected_score(1600, 1200));
+
+            assert_eq!(rate_duel(1200, 1200, Decimal::one(), 32), (1216, 1184));
+            assert_eq!(
+                rate_duel(1200, 1200, Decimal::percent(50), 32),
+                (1200, 1200)
+            );
+            // the favourite gains little from a win and loses a lot from a loss
+            assert_eq!(rate_duel(1600, 1200, Decimal::one(), 32), (1603, 1197));
+            assert_eq!(rate_duel(1600, 1200, Decimal::zero(), 32), (1571, 1229));
+            assert_eq!(
+                rate_duel(1600, 1200, Decimal::percent(50), 32),
+                (1587, 1213)
+            );
+        }
+
+        #[test]
+        fn test_ratings_updated_on_settlement() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            assert_eq!(query_rating(&app, USER1), 1200);
+
+            // only the admin sets a non zero K-factor
+            let err = update_k_factor(&mut app, USER1, 20).unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                ContractError::Unauthorized {}.to_string()
+            );
+            let err = update_k_factor(&mut app, ENIGMA_ADMIN, 0).unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                ContractError::InvalidKFactor {}.to_string()
+            );
+            update_k_factor(&mut app, ENIGMA_ADMIN, 20).unwrap();
+
+            let drawn = create_gr(&mut app);
+            finish_gr(&mut app, drawn, GameRoomStatus::Draw {});
+            assert_eq!(query_rating(&app, USER1), 1200);
+
+            let won = create_gr(&mut app);
+            finish_gr(&mut app, won, GameRoomStatus::Win { addr: USER2.into() });
+            assert_eq!(query_rating(&app, USER1), 1190);
+            assert_eq!(query_rating(&app, USER2), 1210);
+
+            let history: ListRatingHistoryResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListRatingHistory {
+                        player: USER2.into(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            let history: Vec<_> = history
+                .changes
+                .into_iter()
+                .map(|c| {
+                    (
+                        c.game_room_id,
+                        c.change.opponent,
+                        c.change.rating_before,
+                        c.change.rating_after,
+                    )
+                })
+                .collect();
+            assert_eq!(
+                history,
+                vec![
+                    (won, USER1.to_string(), 1200, 1210),
+                    (drawn, USER1.to_string(), 1200, 1200)
+                ]
+            );
+        }
+    }
+
+    mod leaderboard {
+        use super::*;
+
+        use cosmwasm_std::Int128;
+        use msg::{ExecuteMsg, LeaderboardKind, LeaderboardResp, QueryMsg};
+
+        fn play(app: &mut MockApp, contestant1: &str, contestant2: &str, result: GameRoomStatus) {
+            let res = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateGameRoom {
+                        game_room_init_params: GameRoomIntiParams {
+                            contestant1: contestant1.into(),
+                            contestant2: contestant2.into(),
+                            prize_pool: Uint128::new(500000000),
+                            status: GameRoomStatus::Started {},
+                            consents: None,
+                            payout_policy: None,
+                            fee_config: None,
+                        },
+                    },
+                    &[],
+                )
+                .unwrap();
+            let game_room_id = res.events[1].attributes[2].value.parse().unwrap();
+            join_gr(app, contestant1, game_room_id);
+            join_gr(app, contestant2, game_room_id);
+            finish_gr(app, game_room_id, result);
+        }
+
+        fn leaderboard(
+            app: &MockApp,
+            kind: LeaderboardKind,
+            start_after: Option<&str>,
+            limit: Option<u32>,
+        ) -> LeaderboardResp {
+            app.app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::Leaderboard {
+                        kind,
+                        start_after: start_after.map(Into::into),
+                        limit,
+                    },
+                )
+                .unwrap()
+        }
+
+        fn players(resp: LeaderboardResp) -> Vec<String> {
+            resp.entries.into_iter().map(|entry| entry.player).collect()
+        }
+
+        #[test]
+        fn test_leaderboards() {
+            let mut app = get_app();
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER1),
+                    app.edt_addr.clone(),
+                    &test_edt::msg::ExecuteMsg::Transfer {
+                        recipient: USER3.into(),
+                        amount: Uint128::new(1000000000),
+                    },
+                    &[],
+                )
+                .unwrap();
+            for user in [USER1, USER2, USER3] {
+                increase_allowance(&mut app, user);
+                deposit(&mut app, user);
+            }
+
+            let win = |addr: &str| GameRoomStatus::Win { addr: addr.into() };
+            play(&mut app, USER1, USER2, win(USER1));
+            play(&mut app, USER3, USER1, win(USER1));
+            play(&mut app, USER2, USER3, GameRoomStatus::Draw {});
+            play(&mut app, USER2, USER3, win(USER3));
+
+            let by_wins = leaderboard(&app, LeaderboardKind::Wins, None, None);
+            assert_eq!(by_wins.entries[0].stats.wins, 2);
+            assert_eq!(players(by_wins), vec![USER1, USER3, USER2]);
+
+            let by_net = leaderboard(&app, LeaderboardKind::NetWinnings, None, None);
+            assert_eq!(
+                by_net
+                    .entries
+                    .iter()
+                    .map(|entry| entry.stats.net_profit)
+                    .collect::<Vec<_>>(),
+                vec![
+                    Int128::new(100000000),
+                    Int128::new(-200000000),
+                    Int128::new(-500000000)
+                ]
+            );
+            assert_eq!(players(by_net), vec![USER1, USER3, USER2]);
+
+            // the ties are ordered by the address
+            assert_eq!(
+                players(leaderboard(&app, LeaderboardKind::Volume, None, None)),
+                vec![USER3, USER2, USER1]
+            );
+
+            let by_rating = leaderboard(&app, LeaderboardKind::Rating, None, None);
+            assert!(by_rating.entries[0].rating > by_rating.entries[1].rating);
+            assert_eq!(players(by_rating), vec![USER1, USER3, USER2]);
+
+            // paging continues after the last player of the previous page
+            assert_eq!(
+                players(leaderboard(&app, LeaderboardKind::Wins, None, Some(1))),
+                vec![USER1]
+            );
+            assert_eq!(
+                players(leaderboard(
+                    &app,
+                    LeaderboardKind::Wins,
+                    Some(USER1),
+                    Some(1)
+                )),
+                vec![USER3]
+            );
+            assert_eq!(
+                players(leaderboard(
+                    &app,
+                    LeaderboardKind::Rating,
+                    Some(USER3),
+                    None
+                )),
+                vec![USER2]
+            );
+        }
+    }
+
+    mod matchmaking {
+        use super::*;
+
+        use cosmwasm_std::from_json;
+        use msg::{CreateGameRoomResp, ExecuteMsg, GetQueueEntryResp, ListQueueResp, QueryMsg};
+
+        fn join_queue(
+            app: &mut MockApp,
+            player: &str,
+            stake: u128,
+            max_rating_gap: Option<u32>,
+        ) -> anyhow::Result<Option<u64>> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(player),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::JoinQueue {
+                        stake: Uint128::new(stake),
+                        max_rating_gap,
+                    },
+                    &[],
+                )
+                .map(|res| {
+                    res.data
+                        .map(|data| from_json::<CreateGameRoomResp>(data).unwrap().0)
+                })
+        }
+
+        fn leave_queue(app: &mut MockApp, player: &str) -> anyhow::Result<()> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(player),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::LeaveQueue {},
+                    &[],
+                )
+                .map(|_| ())
+        }
+
+        fn queued(app: &MockApp, stake: u128) -> Vec<String> {
+            let resp: ListQueueResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListQueue {
+                        stake: Uint128::new(stake),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            resp.entries
+                .into_iter()
+                .map(|entry| entry.entry.player)
+                .collect()
+        }
+
+        #[test]
+        fn test_queue_pairs_same_stake() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            // the stake must cover the fee
+            assert_err(
+                join_queue(&mut app, USER1, 0, None).unwrap_err(),
+                ContractError::InvalidStake {},
+            );
+            assert_err(
+                join_queue(&mut app, USER1, 50000000, None).unwrap_err(),
+                ContractError::InvalidStake {},
+            );
+
+            assert_eq!(join_queue(&mut app, USER1, 200000000, None).unwrap(), None);
+            assert_eq!(locked(&app, USER1), Uint128::new(200000000));
+            assert_err(
+                join_queue(&mut app, USER1, 300000000, None).unwrap_err(),
+                ContractError::AlreadyQueued {},
+            );
+
+            // another tier doesn't match, leaving gives the stake back
+            assert_eq!(join_queue(&mut app, USER2, 300000000, None).unwrap(), None);
+            assert_eq!(queued(&app, 300000000), vec![USER2]);
+            leave_queue(&mut app, USER2).unwrap();
+            assert_eq!(locked(&app, USER2), Uint128::zero());
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1000000000));
+            assert!(queued(&app, 300000000).is_empty());
+            assert_err(
+                leave_queue(&mut app, USER2).unwrap_err(),
+                ContractError::NotQueued {},
+            );
+
+            let game_room_id = join_queue(&mut app, USER2, 200000000, None)
+                .unwrap()
+                .unwrap();
+            assert!(queued(&app, 200000000).is_empty());
+            let entry: GetQueueEntryResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetQueueEntry {
+                        player: USER1.into(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(entry.entry, None);
+
+            let room: GameRoomsState = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetGameRoomState { game_room_id },
+                )
+                .unwrap();
+            assert_eq!(room.contestant1, USER1);
+            assert_eq!(room.contestant2, USER2);
+            assert_eq!(room.prize_pool, Uint128::new(400000000));
+            assert_eq!(room.status, GameRoomStatus::Started {});
+            assert_eq!(locked(&app, USER2), Uint128::new(200000000));
+
+            // the rooms from the queue settle like any other room
+            finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {});
+            assert_eq!(locked(&app, USER1), Uint128::zero());
+        }
+
+        #[test]
+        fn test_queue_rating_band() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            // 1216 against 1184 after the first duel
+            let game_room_id = join_queue(&mut app, USER1, 200000000, None).unwrap();
+            assert_eq!(game_room_id, None);
+            let game_room_id = join_queue(&mut app, USER2, 200000000, None)
+                .unwrap()
+                .unwrap();
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER1.into() },
+            );
+
+            join_queue(&mut app, USER1, 200000000, Some(10)).unwrap();
+            assert_eq!(join_queue(&mut app, USER2, 200000000, None).unwrap(), None);
+            assert_eq!(queued(&app, 200000000), vec![USER1, USER2]);
+
+            leave_queue(&mut app, USER1).unwrap();
+            join_queue(&mut app, USER1, 200000000, Some(40))
+                .unwrap()
+                .unwrap();
+            assert!(queued(&app, 200000000).is_empty());
+        }
+
+        #[test]
+        fn test_queued_match_conserves_deposits() {
+            let mut app = funded_app();
+
+            join_queue(&mut app, USER1, 500000000, None).unwrap();
+            let game_room_id = join_queue(&mut app, USER2, 500000000, None)
+                .unwrap()
+                .unwrap();
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER2.into() },
+            );
+
+            // the winner takes the stake of the loser minus the fees of both
+            assert_eq!(query_balance(&app, USER1), Uint128::new(500000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1300000000));
+            let ledger: FeeLedger = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetFeeLedger {})
+                .unwrap();
+            assert_eq!(
+                query_balance(&app, USER1)
+                    + query_balance(&app, USER2)
+                    + query_balance(&app, ENIGMA_ADMIN)
+                    + ledger.available(),
+                Uint128::new(2000000000)
+            );
+        }
+    }
+
+    mod challenges {
+        use super::*;
+
+        use cosmwasm_std::{from_json, Timestamp};
+        use msg::{
+            CreateChallengeResp, CreateGameRoomResp, ExecuteMsg, ListChallengesResp, QueryMsg,
+        };
+
+        fn create_challenge(
+            app: &mut MockApp,
+            challenger: &str,
+            opponent: Option<&str>,
+            expires: Timestamp,
+        ) -> anyhow::Result<u64> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(challenger),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateChallenge {
+                        stake: Uint128::new(200000000),
+                        opponent: opponent.map(Into::into),
+                        expires,
+                    },
+                    &[],
+                )
+                .map(|res| {
+                    from_json::<CreateChallengeResp>(res.data.unwrap())
+                        .unwrap()
+                        .0
+                })
+        }
+
+        fn accept_challenge(
+            app: &mut MockApp,
+            sender: &str,
+            challenge_id: u64,
+        ) -> anyhow::Result<u64> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(sender),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::AcceptChallenge { challenge_id },
+                    &[],
+                )
+                .map(|res| {
+                    from_json::<CreateGameRoomResp>(res.data.unwrap())
+                        .unwrap()
+                        .0
+                })
+        }
+
+        fn cancel_challenge(
+            app: &mut MockApp,
+            sender: &str,
+            challenge_id: u64,
+        ) -> anyhow::Result<()> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(sender),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CancelChallenge { challenge_id },
+                    &[],
+                )
+                .map(|_| ())
+        }
+
+        fn open_challenges(app: &MockApp) -> Vec<u64> {
+            let resp: ListChallengesResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListChallenges {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            resp.challenges
+                .into_iter()
+                .map(|challenge| challenge.challenge_id)
+                .collect()
+        }
+
+        #[test]
+        fn test_open_challenge() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let now = app.app.block_info().time;
+            assert_err(
+                create_challenge(&mut app, USER1, None, now).unwrap_err(),
+                ContractError::ChallengeExpired {},
+            );
+
+            let challenge_id =
+                create_challenge(&mut app, USER1, None, now.plus_seconds(100)).unwrap();
+            assert_eq!(locked(&app, USER1), Uint128::new(200000000));
+            assert_eq!(open_challenges(&app), vec![challenge_id]);
+
+            assert_err(
+                accept_challenge(&mut app, USER1, challenge_id).unwrap_err(),
+                ContractError::OwnChallenge {},
+            );
+            let game_room_id = accept_challenge(&mut app, USER2, challenge_id).unwrap();
+            assert_eq!(locked(&app, USER2), Uint128::new(200000000));
+            assert!(open_challenges(&app).is_empty());
+
+            let room: GameRoomsState = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetGameRoomState { game_room_id },
+                )
+                .unwrap();
+            assert_eq!(room.contestant1, USER1);
+            assert_eq!(room.contestant2, USER2);
+            assert_eq!(room.prize_pool, Uint128::new(400000000));
+            assert_eq!(room.status, GameRoomStatus::Started {});
+        }
+
+        #[test]
+        fn test_targeted_challenge_and_cancel() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let expires = app.app.block_info().time.plus_seconds(100);
+            let challenge_id = create_challenge(&mut app, USER1, Some(USER3), expires).unwrap();
+
+            assert_err(
+                accept_challenge(&mut app, USER2, challenge_id).unwrap_err(),
+                ContractError::NotChallengeOpponent {},
+            );
+            // only the challenger cancels before the expiry
+            assert_err(
+                cancel_challenge(&mut app, USER2, challenge_id).unwrap_err(),
+                ContractError::Unauthorized {},
+            );
+
+            app.app.update_block(|block| {
+                block.time = expires;
+            });
+            assert_err(
+                accept_challenge(&mut app, USER3, challenge_id).unwrap_err(),
+                ContractError::ChallengeExpired {},
+            );
+            cancel_challenge(&mut app, USER2, challenge_id).unwrap();
+            assert_eq!(locked(&app, USER1), Uint128::zero());
+            assert!(open_challenges(&app).is_empty());
+            assert!(cancel_challenge(&mut app, USER1, challenge_id).is_err());
+        }
+    }
+
+    mod invites {
+        use super::*;
+
+        use cosmwasm_std::{from_json, Binary};
+        use msg::{CreateGameRoomResp, ExecuteMsg};
+        use sha2::{Digest, Sha256};
+
+        const SECRET: &[u8] = b"duel with me";
+
+        fn create_private_room(app: &mut MockApp, creator: &str) -> u64 {
+            let res = app
+                .app
+                .execute_contract(
+                    Addr::unchecked(creator),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreatePrivateRoom {
+                        stake: Uint128::new(200000000),
+                        secret_hash: Binary::from(Sha256::digest(SECRET).to_vec()),
+                    },
+                    &[],
+                )
+                .unwrap();
+            from_json::<CreateGameRoomResp>(res.data.unwrap())
+                .unwrap()
+                .0
+        }
+
+        fn join(
+            app: &mut MockApp,
+            player: &str,
+            game_room_id: u64,
+            secret: Option<&[u8]>,
+        ) -> anyhow::Result<()> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(player),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::JoinRoom {
+                        game_room_id,
+                        secret: secret.map(Binary::from),
+                    },
+                    &[],
+                )
+                .map(|_| ())
+        }
+
+        #[test]
+        fn test_private_room_requires_secret() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+            increase_allowance(&mut app, USER2);
+            deposit(&mut app, USER2);
+
+            let game_room_id = create_private_room(&mut app, USER1);
+            let room = query_room(&app, game_room_id);
+            assert_eq!(room.contestant2, "");
+            assert_eq!(
+                room.status,
+                GameRoomStatus::Pending {
+                    joined: vec![USER1.to_string()]
+                }
+            );
+
+            for secret in [None, Some(&b"wrong secret"[..])] {
+                let err = join(&mut app, USER2, game_room_id, secret).unwrap_err();
+                assert_eq!(
+                    err.downcast::<ContractError>().unwrap().to_string(),
+                    ContractError::InvalidRoomSecret {}.to_string()
+                );
+            }
+
+            // the secret holder takes the free seat and the stakes are locked
+            join(&mut app, USER2, game_room_id, Some(SECRET)).unwrap();
+            let room = query_room(&app, game_room_id);
+            assert_eq!(room.contestant2, USER2);
+            assert_eq!(room.prize_pool, Uint128::new(400000000));
+            assert_eq!(room.status, GameRoomStatus::Started {});
+
+            // the seat is taken
+            let err = join(&mut app, USER3, game_room_id, Some(SECRET)).unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap().to_string(),
+                ContractError::GameRoomNotPending {}.to_string()
+            );
+
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER2.into() },
+            );
+        }
+    }
+
+    mod ranked {
+        use super::*;
+
+        use cosmwasm_std::from_json;
+        use msg::{CreateGameRoomResp, ExecuteMsg, QueryMsg};
+
+        fn create_ranked_room(
+            app: &mut MockApp,
+            contestants: &[&str],
+            payout_table: Vec<u16>,
+        ) -> anyhow::Result<u64> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateRankedRoom {
+                        contestants: contestants.iter().map(|c| c.to_string()).collect(),
+                        stake: Uint128::new(200000000),
+                        payout_table,
+                    },
+                    &[],
+                )
+                .map(|res| {
+                    from_json::<CreateGameRoomResp>(res.data.unwrap())
+                        .unwrap()
+                        .0
+                })
+        }
+
+        fn ranking(players: &[&str]) -> GameRoomStatus {
+            GameRoomStatus::Ranked {
+                ranking: players.iter().map(|p| p.to_string()).collect(),
+            }
+        }
+
+        #[test]
+        fn test_ranked_room_payout() {
+            let mut app = get_app();
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER1),
+                    app.edt_addr.clone(),
+                    &test_edt::msg::ExecuteMsg::Transfer {
+                        recipient: USER3.into(),
+                        amount: Uint128::new(1000000000),
+                    },
+                    &[],
+                )
+                .unwrap();
+            for user in [USER1, USER2, USER3] {
+                increase_allowance(&mut app, user);
+                deposit(&mut app, user);
+            }
+
+            let invalid_contestants = || ContractError::InvalidContestants { min: 3, max: 10 };
+            assert_err(
+                create_ranked_room(&mut app, &[USER1, USER2], vec![10000]).unwrap_err(),
+                invalid_contestants(),
+            );
+            assert_err(
+                create_ranked_room(&mut app, &[USER1, USER2, USER1], vec![10000]).unwrap_err(),
+                invalid_contestants(),
+            );
+            assert_err(
+                create_ranked_room(&mut app, &[USER1, USER2, USER3], vec![6000, 3000]).unwrap_err(),
+                ContractError::InvalidPayoutTable {},
+            );
+
+            let game_room_id =
+                create_ranked_room(&mut app, &[USER1, USER2, USER3], vec![6000, 3000, 1000])
+                    .unwrap();
+            // the third player finds the room like the first two
+            for user in [USER1, USER2, USER3] {
+                assert_eq!(rooms_of(&app, user), vec![game_room_id]);
+            }
+
+            // the stakes are locked once every contestant joined
+            join_gr(&mut app, USER1, game_room_id);
+            join_gr(&mut app, USER2, game_room_id);
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1000000000));
+            join_gr(&mut app, USER3, game_room_id);
+            assert_eq!(query_balance(&app, USER1), Uint128::new(800000000));
+
+            assert_err(
+                try_finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {}).unwrap_err(),
+                ContractError::InvalidRoomResult {},
+            );
+            assert_err(
+                try_finish_gr(&mut app, game_room_id, ranking(&[USER3, USER1])).unwrap_err(),
+                ContractError::InvalidRanking {},
+            );
+            assert_err(
+                try_finish_gr(&mut app, game_room_id, ranking(&[USER3, USER1, USER1])).unwrap_err(),
+                ContractError::InvalidRanking {},
+            );
+
+            // 600 pool - 3 * 100 fee split 60/30/10
+            try_finish_gr(&mut app, game_room_id, ranking(&[USER3, USER1, USER2])).unwrap();
+            // the winner paid the fee on a 200 stake and took 180 back
+            assert_eq!(
+                query_stats(&app, USER3),
+                state::PlayerStats {
+                    games_played: 1,
+                    wins: 1,
+                    total_wagered: Uint128::new(200000000),
+                    net_profit: cosmwasm_std::Int128::new(-20000000),
+                    fees_paid: Uint128::new(100000000),
+                    ..Default::default()
+                }
+            );
+            assert_eq!(query_stats(&app, USER2).losses, 1);
+            assert_eq!(query_balance(&app, USER3), Uint128::new(980000000));
+            assert_eq!(query_balance(&app, USER1), Uint128::new(890000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(830000000));
+            let fees: Uint128 = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetCollectedFees {})
+                .unwrap();
+            assert_eq!(fees, Uint128::new(300000000));
+
+            // the duels don't take a ranking
+            let duel = create_gr(&mut app);
+            assert_err(
+                try_finish_gr(&mut app, duel, ranking(&[USER1, USER2])).unwrap_err(),
+                ContractError::InvalidRoomResult {},
+            );
+        }
+    }
+
+    mod tournaments {
+        use super::*;
+
+        use contract::DEFAULT_ROOM_TIMEOUT;
+        use msg::{ExecuteMsg, GetTournamentBracketResp, QueryMsg};
+        use state::{Tournament, TournamentStatus};
+
+        fn bracket(app: &MockApp) -> GetTournamentBracketResp {
+            app.app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetTournamentBracket { tournament_id: 1 },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn test_tournament_bracket_and_payout() {
+            let mut app = get_app();
+
+            app.app
+                .execute_contract(
+                    Addr::unchecked(USER1),
+                    app.edt_addr.clone(),
+                    &test_edt::msg::ExecuteMsg::Transfer {
+                        recipient: USER3.into(),
+                        amount: Uint128::new(1000000000),
+                    },
+                    &[],
+                )
+                .unwrap();
+            for user in [USER1, USER2, USER3] {
+                increase_allowance(&mut app, user);
+                deposit(&mut app, user);
+            }
+
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateTournament {
+                    name: "weekly".into(),
+                    entry_fee: Uint128::new(200000000),
+                    max_players: 4,
+                    payout_table: vec![7000, 3000],
+                },
+            )
+            .unwrap();
+
+            let start = ExecuteMsg::StartTournament { tournament_id: 1 };
+            let register = ExecuteMsg::RegisterForTournament { tournament_id: 1 };
+            execute(&mut app, USER1, register.clone()).unwrap();
+            assert_err(
+                execute(&mut app, ENIGMA_ADMIN, start.clone()).unwrap_err(),
+                ContractError::NotEnoughPlayers {},
+            );
+            assert_err(
+                execute(&mut app, USER1, register.clone()).unwrap_err(),
+                ContractError::AlreadyRegistered {},
+            );
+            execute(&mut app, USER2, register.clone()).unwrap();
+            execute(&mut app, USER3, register.clone()).unwrap();
+            assert_eq!(query_balance(&app, USER1), Uint128::new(800000000));
+
+            assert!(execute(&mut app, USER1, start.clone()).is_err());
+            execute(&mut app, ENIGMA_ADMIN, start).unwrap();
+            assert_err(
+                execute(&mut app, USER1, register).unwrap_err(),
+                ContractError::TournamentNotOpen {},
+            );
+
+            // the top seed gets the bye, the other two play
+            let matches = bracket(&app).matches;
+            assert_eq!(matches.len(), 2);
+            assert_eq!(matches[0].tournament_match.player2, None);
+            assert_eq!(matches[0].tournament_match.winner, Some(USER1.to_string()));
+            assert_eq!(matches[1].tournament_match.player1, USER2);
+            assert_eq!(matches[1].tournament_match.player2, Some(USER3.to_string()));
+            let semi_final = matches[1].tournament_match.game_room_id.unwrap();
+
+            // a match outliving the room timeout still waits for its winner
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT);
+            });
+            assert_err(
+                execute(
+                    &mut app,
+                    USER2,
+                    ExecuteMsg::ExpireGameRoom {
+                        game_room_id: semi_final,
+                    },
+                )
+                .unwrap_err(),
+                ContractError::TournamentMatchCantExpire {},
+            );
+
+            assert_err(
+                try_finish_gr(&mut app, semi_final, GameRoomStatus::Draw {}).unwrap_err(),
+                ContractError::TournamentMatchNeedsWinner {},
+            );
+            try_finish_gr(
+                &mut app,
+                semi_final,
+                GameRoomStatus::Win { addr: USER3.into() },
+            )
+            .unwrap();
+
+            let matches = bracket(&app).matches;
+            assert_eq!(matches.len(), 3);
+            assert_eq!(matches[2].round, 2);
+            assert_eq!(matches[2].tournament_match.player1, USER1);
+            let final_room = matches[2].tournament_match.game_room_id.unwrap();
+            try_finish_gr(
+                &mut app,
+                final_room,
+                GameRoomStatus::Win { addr: USER1.into() },
+            )
+            .unwrap();
+
+            let tournament: Tournament = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetTournament { tournament_id: 1 },
+                )
+                .unwrap();
+            assert_eq!(
+                tournament.status,
+                TournamentStatus::Finished {
+                    champion: USER1.into()
+                }
+            );
+
+            // the matches hold no stakes, only their outcome is kept in the stats
+            assert_eq!(
+                query_stats(&app, USER1),
+                state::PlayerStats {
+                    games_played: 1,
+                    wins: 1,
+                    ..Default::default()
+                }
+            );
+            assert_eq!(query_stats(&app, USER3).games_played, 2);
+            assert_eq!(query_stats(&app, USER3).losses, 1);
+
+            // 600 pool - 3 * 100 fee split 70/30 between the finalists
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1010000000));
+            assert_eq!(query_balance(&app, USER3), Uint128::new(890000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(800000000));
+            let fees: Uint128 = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetCollectedFees {})
+                .unwrap();
+            assert_eq!(fees, Uint128::new(300000000));
+        }
+
+        // a two player tournament of the funded users, the final is room 1
+        fn two_player_tournament(app: &mut MockApp) {
+            for user in [USER1, USER2] {
+                increase_allowance(app, user);
+                deposit(app, user);
+            }
+            execute(
+                app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateTournament {
+                    name: "weekly".into(),
+                    entry_fee: Uint128::new(200000000),
+                    max_players: 2,
+                    payout_table: vec![10000],
+                },
+            )
+            .unwrap();
+            for user in [USER1, USER2] {
+                execute(
+                    app,
+                    user,
+                    ExecuteMsg::RegisterForTournament { tournament_id: 1 },
+                )
+                .unwrap();
+            }
+        }
+
+        #[test]
+        fn test_tournament_keeps_creation_fee() {
+            let mut app = get_app();
+            two_player_tournament(&mut app);
+
+            // a fee above the entry fee would leave the pool short
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::UpdateConfig {
+                    fee_config: state::FeeConfig::flat(Uint128::new(300000000)),
+                },
+            )
+            .unwrap();
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::StartTournament { tournament_id: 1 },
+            )
+            .unwrap();
+            finish_gr(&mut app, 1, GameRoomStatus::Win { addr: USER1.into() });
+
+            // 400 pool - 2 * 100 fee to the champion
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1000000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(800000000));
+        }
+
+        #[test]
+        fn test_cancel_tournament_past_deadline() {
+            let mut app = get_app();
+            two_player_tournament(&mut app);
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT / 2);
+            });
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::StartTournament { tournament_id: 1 },
+            )
+            .unwrap();
+
+            // drawing the bracket pushed the deadline back
+            let cancel = ExecuteMsg::CancelTournament { tournament_id: 1 };
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT / 2);
+            });
+            assert_err(
+                execute(&mut app, USER3, cancel.clone()).unwrap_err(),
+                ContractError::MissingRole {
+                    role: msg::Role::Operator,
+                },
+            );
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT / 2);
+            });
+            execute(&mut app, USER3, cancel).unwrap();
+
+            for user in [USER1, USER2] {
+                assert_eq!(query_balance(&app, user), Uint128::new(1000000000));
+                assert_eq!(locked(&app, user), Uint128::zero());
+            }
+            let room: GameRoomsState = query_room(&app, 1);
+            assert_eq!(room.status, GameRoomStatus::Expired {});
+        }
+
+        #[test]
+        fn test_cancel_tournament_refunds() {
+            let mut app = get_app();
+
+            increase_allowance(&mut app, USER1);
+            deposit(&mut app, USER1);
+
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateTournament {
+                    name: "weekly".into(),
+                    entry_fee: Uint128::new(200000000),
+                    max_players: 1,
+                    payout_table: vec![10000],
+                },
+            )
+            .unwrap_err();
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateTournament {
+                    name: "weekly".into(),
+                    entry_fee: Uint128::new(200000000),
+                    max_players: 2,
+                    payout_table: vec![10000],
+                },
+            )
+            .unwrap();
+            execute(
+                &mut app,
+                USER1,
+                ExecuteMsg::RegisterForTournament { tournament_id: 1 },
+            )
+            .unwrap();
+
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CancelTournament { tournament_id: 1 },
+            )
+            .unwrap();
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1000000000));
+            assert_err(
+                execute(
+                    &mut app,
+                    ENIGMA_ADMIN,
+                    ExecuteMsg::CancelTournament { tournament_id: 1 },
+                )
+                .unwrap_err(),
+                ContractError::TournamentNotRunning {},
+            );
+        }
+
+        #[test]
+        fn test_cancel_running_tournament_closes_open_matches() {
+            let mut app = funded_app();
+
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateTournament {
+                    name: "weekly".into(),
+                    entry_fee: Uint128::new(200000000),
+                    max_players: 2,
+                    payout_table: vec![10000],
+                },
+            )
+            .unwrap();
+            for user in [USER1, USER2] {
+                execute(
+                    &mut app,
+                    user,
+                    ExecuteMsg::RegisterForTournament { tournament_id: 1 },
+                )
+                .unwrap();
+            }
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::StartTournament { tournament_id: 1 },
+            )
+            .unwrap();
+            let final_room = bracket(&app).matches[0]
+                .tournament_match
+                .game_room_id
+                .unwrap();
+
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CancelTournament { tournament_id: 1 },
+            )
+            .unwrap();
+
+            // the match nobody will settle is no longer an active room
+            let room: GameRoomsState = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetGameRoomState {
+                        game_room_id: final_room,
+                    },
+                )
+                .unwrap();
+            assert_eq!(room.status, GameRoomStatus::Expired {});
+            let active: msg::ListGameRoomsResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListActiveRooms {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert!(active.rooms.is_empty());
+            for user in [USER1, USER2] {
+                assert_eq!(query_balance(&app, user), Uint128::new(1000000000));
+            }
+        }
+    }
+
+    mod leagues {
+        use super::*;
+
+        use contract::DEFAULT_ROOM_TIMEOUT;
+        use msg::{ExecuteMsg, LeagueFixturesResp, LeagueStandingsResp, QueryMsg};
+        use state::{League, LeagueStatus};
+
+        fn query<T: serde::de::DeserializeOwned>(app: &MockApp, msg: &QueryMsg) -> T {
+            app.app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), msg)
+                .unwrap()
+        }
+
+        #[test]
+        fn test_league_season() {
+            let mut app = get_app();
+
+            fund(&mut app, USER3);
+            fund(&mut app, USER4);
+            for user in [USER1, USER2, USER3, USER4] {
+                increase_allowance(&mut app, user);
+                deposit(&mut app, user);
+            }
+
+            assert_err(
+                execute(
+                    &mut app,
+                    ENIGMA_ADMIN,
+                    ExecuteMsg::CreateLeague {
+                        name: "season".into(),
+                        entry_fee: Uint128::new(200000000),
+                        divisions: 2,
+                        division_size: 2,
+                        movers: 2,
+                        payout_table: vec![10000],
+                    },
+                )
+                .unwrap_err(),
+                ContractError::InvalidLeagueSize {
+                    max_divisions: 4,
+                    max_players: 8,
+                },
+            );
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateLeague {
+                    name: "season".into(),
+                    entry_fee: Uint128::new(200000000),
+                    divisions: 2,
+                    division_size: 2,
+                    movers: 1,
+                    payout_table: vec![10000],
+                },
+            )
+            .unwrap();
+
+            let register = ExecuteMsg::RegisterForLeague { league_id: 1 };
+            for user in [USER1, USER2, USER3, USER4] {
+                execute(&mut app, user, register.clone()).unwrap();
+            }
+            assert_err(
+                execute(&mut app, USER1, register.clone()).unwrap_err(),
+                ContractError::AlreadyRegistered {},
+            );
+            fund(&mut app, "addr7777");
+            increase_allowance(&mut app, "addr7777");
+            deposit(&mut app, "addr7777");
+            assert_err(
+                execute(&mut app, "addr7777", register.clone()).unwrap_err(),
+                ContractError::LeagueFull {},
+            );
+            assert_err(
+                execute(
+                    &mut app,
+                    ENIGMA_ADMIN,
+                    ExecuteMsg::EndLeagueSeason { league_id: 1 },
+                )
+                .unwrap_err(),
+                ContractError::LeagueNotRunning {},
+            );
+
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::StartLeagueSeason { league_id: 1 },
+            )
+            .unwrap();
+            let fixtures: LeagueFixturesResp = query(
+                &app,
+                &QueryMsg::ListLeagueFixtures {
+                    league_id: 1,
+                    season: None,
+                    start_after: None,
+                    limit: None,
+                },
+            );
+            assert_eq!(fixtures.fixtures.len(), 2);
+            assert_eq!(fixtures.fixtures[0].division, 0);
+            assert_eq!(fixtures.fixtures[1].division, 1);
+
+            // a fixture outliving the room timeout still waits for its result
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT);
+            });
+            assert_err(
+                execute(
+                    &mut app,
+                    USER1,
+                    ExecuteMsg::ExpireGameRoom {
+                        game_room_id: fixtures.fixtures[0].game_room_id,
+                    },
+                )
+                .unwrap_err(),
+                ContractError::LeagueFixtureCantExpire {},
+            );
+
+            finish_gr(
+                &mut app,
+                fixtures.fixtures[0].game_room_id,
+                GameRoomStatus::Win { addr: USER2.into() },
+            );
+            finish_gr(
+                &mut app,
+                fixtures.fixtures[1].game_room_id,
+                GameRoomStatus::Draw {},
+            );
+
+            // the fixtures hold no stakes, only their outcome is kept in the stats
+            assert_eq!(
+                query_stats(&app, USER2),
+                state::PlayerStats {
+                    games_played: 1,
+                    wins: 1,
+                    ..Default::default()
+                }
+            );
+
+            // the last fixture closes the season
+            let league: League = query(&app, &QueryMsg::GetLeague { league_id: 1 });
+            assert_eq!(league.season, 2);
+            assert_eq!(league.status, LeagueStatus::Registration {});
+
+            let standings: LeagueStandingsResp = query(
+                &app,
+                &QueryMsg::LeagueStandings {
+                    league_id: 1,
+                    season: Some(1),
+                    division: 0,
+                    start_after: None,
+                    limit: None,
+                },
+            );
+            assert_eq!(standings.standings[0].player, USER2);
+            assert_eq!(standings.standings[0].standing.points, 3);
+            assert_eq!(standings.standings[1].player, USER1);
+            assert_eq!(standings.standings[1].standing.losses, 1);
+            let standings: LeagueStandingsResp = query(
+                &app,
+                &QueryMsg::LeagueStandings {
+                    league_id: 1,
+                    season: Some(1),
+                    division: 1,
+                    start_after: Some(1),
+                    limit: None,
+                },
+            );
+            assert_eq!(standings.standings.len(), 1);
+            assert_eq!(standings.standings[0].position, 2);
+            assert_eq!(standings.standings[0].player, USER4);
+            assert_eq!(standings.standings[0].standing.points, 1);
+
+            // each division pool is the entry fees minus the fee per player
+            assert_eq!(query_balance(&app, USER1), Uint128::new(800000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1000000000));
+            assert_eq!(query_balance(&app, USER3), Uint128::new(1000000000));
+            assert_eq!(query_balance(&app, USER4), Uint128::new(800000000));
+            let fees: Uint128 = query(&app, &QueryMsg::GetCollectedFees {});
+            assert_eq!(fees, Uint128::new(400000000));
+
+            // the bottom of the top division swaps with the top of the one below
+            execute(&mut app, USER1, register.clone()).unwrap();
+            execute(&mut app, USER3, register).unwrap();
+            let standings: LeagueStandingsResp = query(
+                &app,
+                &QueryMsg::LeagueStandings {
+                    league_id: 1,
+                    season: None,
+                    division: 0,
+                    start_after: None,
+                    limit: None,
+                },
+            );
+            assert_eq!(standings.standings.len(), 1);
+            assert_eq!(standings.standings[0].player, USER3);
+        }
+
+        #[test]
+        fn test_end_league_season_expires_open_fixtures() {
+            let mut app = get_app();
+
+            for user in [USER1, USER2] {
+                increase_allowance(&mut app, user);
+                deposit(&mut app, user);
+            }
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateLeague {
+                    name: "season".into(),
+                    entry_fee: Uint128::new(200000000),
+                    divisions: 1,
+                    division_size: 4,
+                    movers: 0,
+                    payout_table: vec![6000, 4000],
+                },
+            )
+            .unwrap();
+            for user in [USER1, USER2] {
+                execute(
+                    &mut app,
+                    user,
+                    ExecuteMsg::RegisterForLeague { league_id: 1 },
+                )
+                .unwrap();
+            }
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::StartLeagueSeason { league_id: 1 },
+            )
+            .unwrap();
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::EndLeagueSeason { league_id: 1 },
+            )
+            .unwrap();
+
+            let room: GameRoomsState = query(&app, &QueryMsg::GetGameRoomState { game_room_id: 1 });
+            assert_eq!(room.status, GameRoomStatus::Expired {});
+            // a scoreless table still pays out by the payout table
+            assert_eq!(query_balance(&app, USER1), Uint128::new(920000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(880000000));
+        }
+
+        // a single division season of the two funded users
+        fn two_player_league(app: &mut MockApp) {
+            for user in [USER1, USER2] {
+                increase_allowance(app, user);
+                deposit(app, user);
+            }
+            execute(
+                app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateLeague {
+                    name: "season".into(),
+                    entry_fee: Uint128::new(200000000),
+                    divisions: 1,
+                    division_size: 2,
+                    movers: 0,
+                    payout_table: vec![6000, 4000],
+                },
+            )
+            .unwrap();
+            for user in [USER1, USER2] {
+                execute(app, user, ExecuteMsg::RegisterForLeague { league_id: 1 }).unwrap();
+            }
+        }
+
+        #[test]
+        fn test_league_keeps_creation_fee() {
+            let mut app = get_app();
+            two_player_league(&mut app);
+
+            // a fee above the entry fee would leave the pool short
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::UpdateConfig {
+                    fee_config: state::FeeConfig::flat(Uint128::new(300000000)),
+                },
+            )
+            .unwrap();
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::StartLeagueSeason { league_id: 1 },
+            )
+            .unwrap();
+            finish_gr(&mut app, 1, GameRoomStatus::Win { addr: USER1.into() });
+
+            let league: League = query(&app, &QueryMsg::GetLeague { league_id: 1 });
+            assert_eq!(league.season, 2);
+            assert_eq!(query_balance(&app, USER1), Uint128::new(920000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(880000000));
+        }
+
+        #[test]
+        fn test_cancel_league() {
+            let mut app = get_app();
+            two_player_league(&mut app);
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::StartLeagueSeason { league_id: 1 },
+            )
+            .unwrap();
+
+            let cancel = ExecuteMsg::CancelLeague { league_id: 1 };
+            assert!(execute(&mut app, USER1, cancel.clone()).is_err());
+            execute(&mut app, ENIGMA_ADMIN, cancel.clone()).unwrap();
+
+            // the entry fees are back and the unplayed fixture is closed
+            for user in [USER1, USER2] {
+                assert_eq!(query_balance(&app, user), Uint128::new(1000000000));
+                assert_eq!(locked(&app, user), Uint128::zero());
+            }
+            let room: GameRoomsState = query(&app, &QueryMsg::GetGameRoomState { game_room_id: 1 });
+            assert_eq!(room.status, GameRoomStatus::Expired {});
+            let league: League = query(&app, &QueryMsg::GetLeague { league_id: 1 });
+            assert_eq!(league.status, LeagueStatus::Cancelled {});
+
+            assert_err(
+                execute(&mut app, ENIGMA_ADMIN, cancel).unwrap_err(),
+                ContractError::LeagueNotRunning {},
+            );
+            assert_err(
+                execute(
+                    &mut app,
+                    USER1,
+                    ExecuteMsg::RegisterForLeague { league_id: 1 },
+                )
+                .unwrap_err(),
+                ContractError::LeagueNotOpen {},
+            );
+        }
+
+        #[test]
+        fn test_cancel_league_past_deadline() {
+            let mut app = get_app();
+            two_player_league(&mut app);
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT / 2);
+            });
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::StartLeagueSeason { league_id: 1 },
+            )
+            .unwrap();
+
+            // starting the season pushed the deadline back
+            let cancel = ExecuteMsg::CancelLeague { league_id: 1 };
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT / 2);
+            });
+            assert_err(
+                execute(&mut app, USER3, cancel.clone()).unwrap_err(),
+                ContractError::MissingRole {
+                    role: msg::Role::Operator,
+                },
+            );
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT / 2);
+            });
+            execute(&mut app, USER3, cancel).unwrap();
+
+            for user in [USER1, USER2] {
+                assert_eq!(query_balance(&app, user), Uint128::new(1000000000));
+                assert_eq!(locked(&app, user), Uint128::zero());
+            }
+            let room: GameRoomsState = query(&app, &QueryMsg::GetGameRoomState { game_room_id: 1 });
+            assert_eq!(room.status, GameRoomStatus::Expired {});
+        }
+    }
+
+    mod series {
+        use super::*;
+
+        use contract::DEFAULT_ROOM_TIMEOUT;
+        use msg::{ExecuteMsg, QueryMsg, SeriesScoreResp};
+
+        fn create_series(app: &mut MockApp, best_of: u32) -> anyhow::Result<()> {
+            execute(
+                app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::CreateSeriesRoom {
+                    contestant1: USER1.into(),
+                    contestant2: USER2.into(),
+                    stake: Uint128::new(250000000),
+                    best_of,
+                },
+            )
+        }
+
+        fn report(app: &mut MockApp, result: GameRoomStatus) -> anyhow::Result<()> {
+            execute(
+                app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::ReportSeriesGame {
+                    game_room_id: 1,
+                    result,
+                },
+            )
+        }
+
+        fn query_score(app: &MockApp) -> SeriesScoreResp {
+            app.app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetSeriesScore { game_room_id: 1 },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn test_series_settles_on_required_wins() {
+            let mut app = setup(&[USER1, USER2], |app| create_series(app, 3));
+
+            assert_err(
+                execute(
+                    &mut app,
+                    ENIGMA_ADMIN,
+                    ExecuteMsg::FinishGameRoom {
+                        game_room_finish_params: GameRoomFinishParams {
+                            game_room_id: 1,
+                            result: GameRoomStatus::Draw {},
+                        },
+                    },
+                )
+                .unwrap_err(),
+                ContractError::SeriesInProgress {},
+            );
+            assert_err(
+                report(&mut app, GameRoomStatus::Win { addr: USER3.into() }).unwrap_err(),
+                ContractError::NotAContestant {},
+            );
+
+            report(&mut app, GameRoomStatus::Win { addr: USER1.into() }).unwrap();
+            report(&mut app, GameRoomStatus::Draw {}).unwrap();
+            let score = query_score(&app);
+            assert_eq!(score.contestant1_wins, 1);
+            assert_eq!(score.contestant2_wins, 0);
+            assert_eq!(score.draws, 1);
+            assert_eq!(score.result, None);
+            // the stakes stay locked during the series
+            assert_eq!(query_balance(&app, USER2), Uint128::new(750000000));
+
+            report(&mut app, GameRoomStatus::Win { addr: USER1.into() }).unwrap();
+            let score = query_score(&app);
+            assert_eq!(score.games.len(), 3);
+            assert_eq!(
+                score.result,
+                Some(GameRoomStatus::Win { addr: USER1.into() })
+            );
+            assert_eq!(query_balance(&app, USER2), Uint128::new(750000000));
+            let fees: Uint128 = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetCollectedFees {})
+                .unwrap();
+            assert_eq!(fees, Uint128::new(200000000));
+            assert_err(
+                report(&mut app, GameRoomStatus::Draw {}).unwrap_err(),
+                ContractError::GameRoomNotStarted {},
+            );
+        }
+
+        #[test]
+        fn test_series_ends_drawn() {
+            let mut app = get_app();
+            assert_err(
+                create_series(&mut app, 4).unwrap_err(),
+                ContractError::InvalidBestOf { max: 9 },
+            );
+
+            let mut app = setup(&[USER1, USER2], |app| create_series(app, 3));
+            report(&mut app, GameRoomStatus::Win { addr: USER2.into() }).unwrap();
+            report(&mut app, GameRoomStatus::Win { addr: USER1.into() }).unwrap();
+            report(&mut app, GameRoomStatus::Draw {}).unwrap();
+
+            assert_eq!(query_score(&app).result, Some(GameRoomStatus::Draw {}));
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1000000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1000000000));
+        }
+
+        #[test]
+        fn test_series_leader_wins_after_the_last_game() {
+            let mut app = setup(&[USER1, USER2], |app| create_series(app, 3));
+            report(&mut app, GameRoomStatus::Win { addr: USER2.into() }).unwrap();
+            report(&mut app, GameRoomStatus::Draw {}).unwrap();
+            assert_eq!(query_score(&app).result, None);
+            report(&mut app, GameRoomStatus::Draw {}).unwrap();
+
+            // a single win out of three still decides the series
+            assert_eq!(
+                query_score(&app).result,
+                Some(GameRoomStatus::Win { addr: USER2.into() })
+            );
+            assert_eq!(query_balance(&app, USER1), Uint128::new(750000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1050000000));
+        }
+
+        #[test]
+        fn test_series_deadline_follows_the_games() {
+            let mut app = setup(&[USER1, USER2], |app| create_series(app, 3));
+            let expire = ExecuteMsg::ExpireGameRoom { game_room_id: 1 };
+
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT / 2);
+            });
+            report(&mut app, GameRoomStatus::Win { addr: USER1.into() }).unwrap();
+
+            // the trailing player can't get their stake back past the creation deadline
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT / 2);
+            });
+            assert_err(
+                execute(&mut app, USER2, expire.clone()).unwrap_err(),
+                ContractError::GameRoomDeadlineNotReached {},
+            );
+            report(&mut app, GameRoomStatus::Draw {}).unwrap();
+
+            // but a series the operators stopped reporting still refunds
+            app.app.update_block(|block| {
+                block.time = block.time.plus_seconds(DEFAULT_ROOM_TIMEOUT);
+            });
+            execute(&mut app, USER2, expire).unwrap();
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1000000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1000000000));
+        }
+    }
+
+    mod handicap {
+        use super::*;
+
+        use cosmwasm_std::Int128;
+        use cw_multi_test::AppResponse;
+        use msg::{ExecuteMsg, GetPlayerStatsResp, QueryMsg};
+
+        fn create_handicap_gr(
+            app: &mut MockApp,
+            stake1: Uint128,
+            stake2: Uint128,
+        ) -> anyhow::Result<AppResponse> {
+            app.app.execute_contract(
+                Addr::unchecked(ENIGMA_ADMIN),
+                app.enigma_addr.clone(),
+                &ExecuteMsg::CreateHandicapRoom {
+                    contestant1: USER1.into(),
+                    contestant2: USER2.into(),
+                    stake1,
+                    stake2,
+                },
+                &[],
+            )
+        }
+
+        // the stronger player stakes twice as much
+        fn create_uneven_gr(app: &mut MockApp) -> anyhow::Result<AppResponse> {
+            create_handicap_gr(app, Uint128::new(600000000), Uint128::new(300000000))
+        }
+
+        #[test]
+        fn test_handicap_win_takes_the_pool() {
+            let mut app = setup(&[USER1, USER2], create_uneven_gr);
+            assert_eq!(query_balance(&app, USER1), Uint128::new(400000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(700000000));
+
+            finish_gr(&mut app, 1, GameRoomStatus::Win { addr: USER1.into() });
+
+            // 900 pool - 2 * 100 fee
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1100000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(700000000));
+            assert_eq!(query_collected_fees(&app), Uint128::new(200000000));
+
+            let stats: GetPlayerStatsResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetPlayerStats {
+                        player: USER2.into(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(stats.stats.total_wagered, Uint128::new(300000000));
+            assert_eq!(stats.stats.net_profit, Int128::new(-300000000));
+        }
+
+        #[test]
+        fn test_handicap_win_by_stranger() {
+            let mut app = setup(&[USER1, USER2], create_uneven_gr);
+            let err =
+                try_finish_gr(&mut app, 1, GameRoomStatus::Win { addr: USER3.into() }).unwrap_err();
+            assert_err(err, ContractError::NotAContestant {});
+            assert_eq!(locked(&app, USER2), Uint128::new(300000000));
+        }
+
+        #[test]
+        fn test_handicap_draw_refunds_own_stakes() {
+            let mut app = setup(&[USER1, USER2], create_uneven_gr);
+            finish_gr(&mut app, 1, GameRoomStatus::Draw {});
+
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1000000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1000000000));
+
+            // the lower stake must cover the fees of both contestants
+            assert_err(
+                create_handicap_gr(&mut app, Uint128::new(600000000), Uint128::new(200000000))
+                    .unwrap_err(),
+                ContractError::InvalidStake {},
+            );
+        }
+    }
+
+    mod scoring {
+        use super::*;
+
+        use msg::{ExecuteMsg, PayoutPolicy};
+
+        fn create_scored_gr(app: &mut MockApp, policy: PayoutPolicy) -> anyhow::Result<()> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateGameRoom {
+                        game_room_init_params: GameRoomIntiParams {
+                            contestant1: USER1.into(),
+                            contestant2: USER2.into(),
+                            prize_pool: Uint128::new(1500000000),
+                            status: GameRoomStatus::Started {},
+                            consents: None,
+                            payout_policy: Some(policy),
+                            fee_config: None,
+                        },
+                    },
+                    &[],
+                )
+                .map(|_| ())
+        }
+
+        fn finish_scored(app: &mut MockApp, score1: u32, score2: u32) -> anyhow::Result<()> {
+            try_finish_gr(
+                app,
+                1,
+                GameRoomStatus::Scored {
+                    scores: vec![(USER2.into(), score2), (USER1.into(), score1)],
+                },
+            )
+        }
+
+        fn assert_balances(app: &MockApp, balance1: u128, balance2: u128) {
+            assert_eq!(query_balance(app, USER1), Uint128::new(balance1));
+            assert_eq!(query_balance(app, USER2), Uint128::new(balance2));
+        }
+
+        #[test]
+        fn test_winner_takes_all() {
+            let mut app = setup(&[USER1, USER2], |app| {
+                create_scored_gr(app, PayoutPolicy::WinnerTakesAll {})
+            });
+            finish_scored(&mut app, 4, 7).unwrap();
+
+            // 1500 pool - 2 * 100 fee
+            assert_balances(&app, 250000000, 1550000000);
+            let room = query_room(&app, 1);
+            assert_eq!(room.status.kind(), msg::GameRoomStatusKind::Scored);
+        }
+
+        #[test]
+        fn test_proportional_payout() {
+            let mut app = setup(&[USER1, USER2], |app| {
+                create_scored_gr(app, PayoutPolicy::Proportional {})
+            });
+            finish_scored(&mut app, 9, 1).unwrap();
+
+            assert_balances(&app, 1420000000, 380000000);
+        }
+
+        #[test]
+        fn test_margin_refund() {
+            let policy = PayoutPolicy::MarginRefund {
+                margin: 2,
+                refund: 5000,
+            };
+            let mut app = setup(&[USER1, USER2], |app| create_scored_gr(app, policy.clone()));
+            finish_scored(&mut app, 10, 9).unwrap();
+            // the close loser takes half of the stake back
+            assert_balances(&app, 1175000000, 625000000);
+
+            let mut app = setup(&[USER1, USER2], |app| create_scored_gr(app, policy));
+            finish_scored(&mut app, 10, 5).unwrap();
+            assert_balances(&app, 1550000000, 250000000);
+        }
+
+        #[test]
+        fn test_equal_scores_refund() {
+            let mut app = setup(&[USER1, USER2], |app| {
+                create_scored_gr(app, PayoutPolicy::Proportional {})
+            });
+
+            assert_err(
+                try_finish_gr(
+                    &mut app,
+                    1,
+                    GameRoomStatus::Scored {
+                        scores: vec![(USER1.into(), 3), (USER1.into(), 3)],
+                    },
+                )
+                .unwrap_err(),
+                ContractError::InvalidScores {},
+            );
+
+            finish_scored(&mut app, 5, 5).unwrap();
+            assert_balances(&app, 1000000000, 1000000000);
+
+            assert_err(
+                create_scored_gr(
+                    &mut app,
+                    PayoutPolicy::MarginRefund {
+                        margin: 1,
+                        refund: 10001,
+                    },
+                )
+                .unwrap_err(),
+                ContractError::InvalidPayoutPolicy {},
+            );
+        }
+    }
+
+    mod teams {
+        use super::*;
+
+        use msg::{ExecuteMsg, QueryMsg, TeamMember};
+
+        fn member(addr: &str, stake: u128) -> TeamMember {
+            TeamMember {
+                addr: addr.into(),
+                stake: Uint128::new(stake),
+            }
+        }
+
+        fn create_team_gr(
+            app: &mut MockApp,
+            team1: Vec<TeamMember>,
+            team2: Vec<TeamMember>,
+        ) -> anyhow::Result<()> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateTeamRoom { team1, team2 },
+                    &[],
+                )
+                .map(|_| ())
+        }
+
+        fn create_teams(app: &mut MockApp) -> anyhow::Result<()> {
+            create_team_gr(
+                app,
+                vec![member(USER1, 600000000), member(USER2, 200000000)],
+                vec![member(USER3, 400000000), member(USER4, 400000000)],
+            )
+        }
+
+        #[test]
+        fn test_team_room_listed_for_every_member() {
+            let app = setup(&[USER1, USER2, USER3, USER4], create_teams);
+            for user in [USER1, USER2, USER3, USER4] {
+                assert_eq!(rooms_of(&app, user), vec![1]);
+            }
+        }
+
+        #[test]
+        fn test_team_win_pays_pro_rata() {
+            let mut app = setup(&[USER1, USER2, USER3, USER4], create_teams);
+            assert_eq!(query_balance(&app, USER1), Uint128::new(400000000));
+
+            assert_err(
+                try_finish_gr(&mut app, 1, GameRoomStatus::Win { addr: USER1.into() }).unwrap_err(),
+                ContractError::InvalidRoomResult {},
+            );
+            assert_err(
+                try_finish_gr(&mut app, 1, GameRoomStatus::TeamWin { team: 3 }).unwrap_err(),
+                ContractError::InvalidRoomResult {},
+            );
+            try_finish_gr(&mut app, 1, GameRoomStatus::TeamWin { team: 1 }).unwrap();
+
+            // 800 losing stakes - 4 * 100 fee shared 3:1
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1300000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(1100000000));
+            assert_eq!(query_balance(&app, USER3), Uint128::new(600000000));
+            assert_eq!(query_balance(&app, USER4), Uint128::new(600000000));
+            assert_eq!(query_collected_fees(&app), Uint128::new(400000000));
+            assert_eq!(
+                query_stats(&app, USER1),
+                state::PlayerStats {
+                    games_played: 1,
+                    wins: 1,
+                    total_wagered: Uint128::new(600000000),
+                    net_profit: cosmwasm_std::Int128::new(300000000),
+                    fees_paid: Uint128::new(100000000),
+                    ..Default::default()
+                }
+            );
+            assert_eq!(
+                query_stats(&app, USER4).net_profit,
+                cosmwasm_std::Int128::new(-400000000)
+            );
+
+            // the members played against the other side
+            let history: msg::ListPlayerHistoryResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::ListPlayerHistory {
+                        player: USER1.into(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(history.matches[0].record.opponent, "");
+            assert_eq!(
+                history.matches[0].record.opponents,
+                vec![USER3.to_string(), USER4.to_string()]
+            );
+        }
+
+        #[test]
+        fn test_team_draw_refunds_stakes() {
+            let mut app = setup(&[USER1, USER2, USER3, USER4], create_teams);
+            try_finish_gr(&mut app, 1, GameRoomStatus::Draw {}).unwrap();
+
+            for user in [USER1, USER2, USER3, USER4] {
+                assert_eq!(query_balance(&app, user), Uint128::new(1000000000));
+            }
+
+            assert_err(
+                create_team_gr(
+                    &mut app,
+                    vec![member(USER1, 600000000), member(USER2, 200000000)],
+                    vec![member(USER1, 400000000)],
+                )
+                .unwrap_err(),
+                ContractError::InvalidTeams { max: 5 },
+            );
+            // the side of a single player can't cover the fee of three
+            assert_err(
+                create_team_gr(
+                    &mut app,
+                    vec![member(USER1, 600000000), member(USER2, 200000000)],
+                    vec![member(USER3, 200000000)],
+                )
+                .unwrap_err(),
+                ContractError::InvalidStake {},
+            );
+        }
+    }
+
+    mod fees {
+        use super::*;
+
+        use msg::{
+            ConfigResp, ExecuteMsg, QueryMsg, Role, SimulateRoomResp, SimulatedOutcome,
+            SimulationAmount,
+        };
+        use state::{DrawFeePolicy, FeeConfig, FeeTier};
+
+        fn update_config(
+            app: &mut MockApp,
+            sender: &str,
+            fee_config: FeeConfig,
+        ) -> anyhow::Result<()> {
+            app.app
+                .execute_contract(
+                    Addr::unchecked(sender),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::UpdateConfig { fee_config },
+                    &[],
+                )
+                .map(|_| ())
+        }
+
+        #[test]
+        fn test_update_config() {
+            let mut app = funded_app();
+
+            // instantiated with the flat fee
+            let config: ConfigResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetConfig {})
+                .unwrap();
+            assert_eq!(config.fee_config, FeeConfig::flat(Uint128::new(100000000)));
+
+            let fee_config = FeeConfig {
+                bps: 250,
+                min: Some(Uint128::new(10000000)),
+                ..FeeConfig::flat(Uint128::zero())
+            };
+            let err = update_config(&mut app, USER1, fee_config.clone()).unwrap_err();
+            assert_err(err, ContractError::Unauthorized {});
+
+            for invalid in [
+                FeeConfig {
+                    bps: 10001,
+                    ..fee_config.clone()
+                },
+                FeeConfig {
+                    max: Some(Uint128::new(1)),
+                    ..fee_config.clone()
+                },
+                FeeConfig {
+                    tiers: vec![
+                        FeeTier {
+                            min_stake: Uint128::new(500),
+                            flat: Uint128::zero(),
+                            bps: 100,
+                        },
+                        FeeTier {
+                            min_stake: Uint128::new(100),
+                            flat: Uint128::zero(),
+                            bps: 50,
+                        },
+                    ],
+                    ..fee_config.clone()
+                },
+            ] {
+                let err = update_config(&mut app, ENIGMA_ADMIN, invalid).unwrap_err();
+                assert_err(err, ContractError::InvalidFeeConfig {});
+            }
+
+            update_config(&mut app, ENIGMA_ADMIN, fee_config.clone()).unwrap();
+            let config: ConfigResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetConfig {})
+                .unwrap();
+            assert_eq!(config.fee_config, fee_config);
+        }
+
+        #[test]
+        fn test_bps_fee_with_caps() {
+            let mut app = funded_app();
+            // 5% of the 750 stake is capped at 30
+            update_config(
+                &mut app,
+                ENIGMA_ADMIN,
+                FeeConfig {
+                    bps: 500,
+                    min: Some(Uint128::new(10000000)),
+                    max: Some(Uint128::new(30000000)),
+                    ..FeeConfig::flat(Uint128::zero())
+                },
+            )
+            .unwrap();
+
+            let game_room_id = create_gr(&mut app);
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER1.into() },
+            );
+
+            assert_eq!(query_collected_fees(&app), Uint128::new(60000000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(250000000));
+        }
+
+        #[test]
+        fn test_tiered_fee() {
+            let mut app = funded_app();
+            update_config(
+                &mut app,
+                ENIGMA_ADMIN,
+                FeeConfig {
+                    bps: 1000,
+                    tiers: vec![FeeTier {
+                        min_stake: Uint128::new(500000000),
+                        flat: Uint128::new(1000000),
+                        bps: 0,
+                    }],
+                    ..FeeConfig::flat(Uint128::zero())
+                },
+            )
+            .unwrap();
+
+            let game_room_id = create_gr(&mut app);
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER2.into() },
+            );
+
+            // the 750 stakes fall in the tier of the large stakes
+            assert_eq!(query_collected_fees(&app), Uint128::new(2000000));
+        }
+
+        #[test]
+        fn test_charged_draw() {
+            let mut app = funded_app();
+            update_config(
+                &mut app,
+                ENIGMA_ADMIN,
+                FeeConfig {
+                    bps: 100,
+                    draw_policy: DrawFeePolicy::Charged {},
+                    ..FeeConfig::flat(Uint128::zero())
+                },
+            )
+            .unwrap();
+
+            let game_room_id = create_gr(&mut app);
+            finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {});
+
+            assert_eq!(query_balance(&app, USER1), Uint128::new(992500000));
+            assert_eq!(query_balance(&app, USER2), Uint128::new(992500000));
+            assert_eq!(query_collected_fees(&app), Uint128::new(15000000));
+        }
+
+        #[test]
+        fn test_room_override() {
+            let mut app = funded_app();
+
+            let create = |app: &mut MockApp, sender: &str, fee_config: FeeConfig| {
+                app.app.execute_contract(
+                    Addr::unchecked(sender),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::CreateGameRoom {
+                        game_room_init_params: GameRoomIntiParams {
+                            contestant1: USER1.into(),
+                            contestant2: USER2.into(),
+                            prize_pool: Uint128::new(1500000000),
+                            status: GameRoomStatus::Started {},
+                            consents: None,
+                            payout_policy: None,
+                            fee_config: Some(fee_config),
+                        },
+                    },
+                    &[],
+                )
+            };
+
+            // an operator can't lower the fees of its own rooms
+            app.app
+                .execute_contract(
+                    Addr::unchecked(ENIGMA_ADMIN),
+                    app.enigma_addr.clone(),
+                    &ExecuteMsg::GrantRole {
+                        role: Role::Operator,
+                        addr: USER3.into(),
+                    },
+                    &[],
+                )
+                .unwrap();
+            let err = create(&mut app, USER3, FeeConfig::flat(Uint128::zero())).unwrap_err();
+            assert_err(err, ContractError::Unauthorized {});
+
+            let err = create(
+                &mut app,
+                ENIGMA_ADMIN,
+                FeeConfig::flat(Uint128::new(400000000)),
+            )
+            .unwrap_err();
+            assert_err(err, ContractError::InvalidStake {});
+
+            create(
+                &mut app,
+                ENIGMA_ADMIN,
+                FeeConfig::flat(Uint128::new(50000000)),
+            )
+            .unwrap();
+            join_gr(&mut app, USER1, 1);
+            join_gr(&mut app, USER2, 1);
+            // the contract wide fee changing afterwards doesn't touch the room
+            update_config(&mut app, ENIGMA_ADMIN, FeeConfig::flat(Uint128::new(1))).unwrap();
+            finish_gr(&mut app, 1, GameRoomStatus::Win { addr: USER1.into() });
+
+            assert_eq!(query_collected_fees(&app), Uint128::new(100000000));
+        }
+
+        #[test]
+        fn test_room_keeps_creation_fee() {
+            let mut app = funded_app();
+
+            let game_room_id = create_gr(&mut app);
+            // a fee above the half stake would leave nothing to pay the winner with
+            update_config(
+                &mut app,
+                ENIGMA_ADMIN,
+                FeeConfig::flat(Uint128::new(400000000)),
+            )
+            .unwrap();
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER1.into() },
+            );
+
+            // the room settled with the fee its contestants joined with
+            assert_eq!(query_collected_fees(&app), Uint128::new(200000000));
+            assert_eq!(query_balance(&app, USER1), Uint128::new(1550000000));
+        }
+
+        #[test]
+        fn test_simulate_room() {
+            let mut app = funded_app();
+
+            let simulate = |app: &MockApp, amount: SimulationAmount, contestants: [&str; 2]| {
+                app.app.wrap().query_wasm_smart::<SimulateRoomResp>(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::SimulateRoom {
+                        amount,
+                        contestants: contestants.map(String::from).to_vec(),
+                    },
+                )
+            };
+
+            let resp = simulate(
+                &app,
+                SimulationAmount::Stake {
+                    amount: Uint128::new(750000000),
+                },
+                [USER1, USER3],
+            )
+            .unwrap();
+            assert_eq!(resp.prize_pool, Uint128::new(1500000000));
+            assert_eq!(resp.fee, Uint128::new(100000000));
+            assert_eq!(resp.draw_fee, Uint128::zero());
+            assert_eq!(
+                resp.contestants
+                    .iter()
+                    .map(|contestant| (contestant.required_lock, contestant.sufficient))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (Uint128::new(750000000), true),
+                    (Uint128::new(750000000), false)
+                ]
+            );
+            assert_eq!(
+                resp.outcomes[1],
+                SimulatedOutcome {
+                    result: GameRoomStatus::Win { addr: USER3.into() },
+                    payouts: vec![Uint128::zero(), Uint128::new(1300000000)],
+                    fees: Uint128::new(200000000),
+                }
+            );
+            assert_eq!(
+                resp.outcomes[2],
+                SimulatedOutcome {
+                    result: GameRoomStatus::Draw {},
+                    payouts: vec![Uint128::new(750000000); 2],
+                    fees: Uint128::zero(),
+                }
+            );
+
+            // the simulation follows the fee schedule
+            update_config(
+                &mut app,
+                ENIGMA_ADMIN,
+                FeeConfig {
+                    bps: 100,
+                    draw_policy: DrawFeePolicy::Charged {},
+                    ..FeeConfig::flat(Uint128::zero())
+                },
+            )
+            .unwrap();
+            let resp = simulate(
+                &app,
+                SimulationAmount::PrizePool {
+                    amount: Uint128::new(1500000000),
+                },
+                [USER1, USER2],
+            )
+            .unwrap();
+            assert_eq!(resp.stake, Uint128::new(750000000));
+            assert_eq!(resp.outcomes[2].payouts, vec![Uint128::new(742500000); 2]);
+            assert_eq!(resp.outcomes[2].fees, Uint128::new(15000000));
+
+            let game_room_id = create_gr(&mut app);
+            finish_gr(&mut app, game_room_id, GameRoomStatus::Draw {});
+            assert_eq!(
+                query_balance(&app, USER1),
+                Uint128::new(1000000000) - resp.draw_fee
+            );
+
+            simulate(
+                &app,
+                SimulationAmount::Stake {
+                    amount: Uint128::new(1),
+                },
+                [USER1, USER1],
+            )
+            .unwrap_err();
+
+            // a ranked room settles by its payout table, not as a duel
+            let err = app
+                .app
+                .wrap()
+                .query_wasm_smart::<SimulateRoomResp>(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::SimulateRoom {
+                        amount: SimulationAmount::Stake {
+                            amount: Uint128::new(100000000),
+                        },
+                        contestants: vec![USER1.into(), USER2.into(), USER3.into()],
+                    },
+                )
+                .unwrap_err();
+            assert!(err
+                .to_string()
+                .contains(&ContractError::UnsupportedRoomKind {}.to_string()));
+        }
+
+        #[test]
+        fn test_simulated_win_matches_settlement() {
+            let mut app = funded_app();
+
+            update_config(
+                &mut app,
+                ENIGMA_ADMIN,
+                FeeConfig {
+                    bps: 250,
+                    ..FeeConfig::flat(Uint128::new(10000000))
+                },
+            )
+            .unwrap();
+            let resp: SimulateRoomResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::SimulateRoom {
+                        amount: SimulationAmount::PrizePool {
+                            amount: Uint128::new(1500000000),
+                        },
+                        contestants: vec![USER1.into(), USER2.into()],
+                    },
+                )
+                .unwrap();
+            let outcome = resp.outcomes[0].clone();
+            assert_eq!(outcome.result, GameRoomStatus::Win { addr: USER1.into() });
+
+            let game_room_id = create_gr(&mut app);
+            finish_gr(&mut app, game_room_id, outcome.result);
+
+            // each contestant spent the stake and got the simulated payout back
+            for (user, payout) in [USER1, USER2].into_iter().zip(outcome.payouts) {
+                assert_eq!(
+                    query_balance(&app, user),
+                    Uint128::new(1000000000) - resp.stake + payout
+                );
+            }
+            let ledger: FeeLedger = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetFeeLedger {})
+                .unwrap();
+            assert_eq!(ledger.accrued, outcome.fees);
+        }
+    }
+
+    mod fee_recipients {
+        use super::*;
+
+        use msg::{ExecuteMsg, GetReferrerResp, ListFeeRecipientsResp, QueryMsg};
+        use state::{FeeDestination, FeeRecipient};
+
+        const TREASURY: &str = "addr8888";
+        const JACKPOT: &str = "addr9999";
+
+        fn recipient(name: &str, destination: FeeDestination, weight: u32) -> FeeRecipient {
+            FeeRecipient {
+                name: name.into(),
+                destination,
+                weight,
+            }
+        }
+
+        fn recipients() -> Vec<FeeRecipient> {
+            vec![
+                recipient(
+                    "treasury",
+                    FeeDestination::Balance {
+                        addr: TREASURY.into(),
+                    },
+                    5,
+                ),
+                recipient(
+                    "jackpot",
+                    FeeDestination::Balance {
+                        addr: JACKPOT.into(),
+                    },
+                    2,
+                ),
+                recipient("burn", FeeDestination::Burn {}, 2),
+                recipient("referrers", FeeDestination::Referrer {}, 1),
+            ]
+        }
+
+        fn grant_referrer(app: &mut MockApp, addr: &str) {
+            execute(
+                app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::GrantRole {
+                    role: msg::Role::Referrer,
+                    addr: addr.into(),
+                },
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn test_update_fee_recipients() {
+            let mut app = get_app();
+
+            let update = |recipients| ExecuteMsg::UpdateFeeRecipients { recipients };
+            let err = execute(&mut app, USER1, update(recipients())).unwrap_err();
+            assert_err(err, ContractError::Unauthorized {});
+
+            let mut duplicated = recipients();
+            duplicated[1].name = "treasury".into();
+            let mut weightless = recipients();
+            weightless[2].weight = 0;
+            let mut referrers = recipients();
+            referrers[0].destination = FeeDestination::Referrer {};
+            let mut shared = recipients();
+            shared[1].destination = shared[0].destination.clone();
+            for recipients in [duplicated, weightless, referrers, shared] {
+                let err = execute(&mut app, ENIGMA_ADMIN, update(recipients)).unwrap_err();
+                assert_err(err, ContractError::InvalidFeeRecipients { max: 10 });
+            }
+
+            execute(&mut app, ENIGMA_ADMIN, update(recipients())).unwrap();
+            let resp: ListFeeRecipientsResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::ListFeeRecipients {})
+                .unwrap();
+            assert_eq!(resp.recipients.len(), 4);
+            assert_eq!(resp.recipients[0].recipient, recipients()[0]);
+            assert_eq!(resp.pending_burn, Uint128::zero());
+        }
+
+        #[test]
+        fn test_set_referrer() {
+            let mut app = get_app();
+
+            let set = |referrer: &str| ExecuteMsg::SetReferrer {
+                referrer: referrer.into(),
+            };
+            // only the referrers vetted by the admin can be named
+            let err = execute(&mut app, USER2, set(USER3)).unwrap_err();
+            assert_err(err, ContractError::InvalidReferrer {});
+            for user in [USER2, USER3] {
+                grant_referrer(&mut app, user);
+            }
+            let err = execute(&mut app, USER2, set(USER2)).unwrap_err();
+            assert_err(err, ContractError::InvalidReferrer {});
+
+            execute(&mut app, USER2, set(USER3)).unwrap();
+            let err = execute(&mut app, USER2, set(USER1)).unwrap_err();
+            assert_err(err, ContractError::InvalidReferrer {});
+
+            let resp: GetReferrerResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    app.enigma_addr.clone(),
+                    &QueryMsg::GetReferrer {
+                        player: USER2.into(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp.referrer, Some(USER3.into()));
+        }
+
+        #[test]
+        fn test_split_fees() {
+            let mut app = funded_app();
+            execute(
+                &mut app,
+                ENIGMA_ADMIN,
+                ExecuteMsg::UpdateFeeRecipients {
+                    recipients: recipients(),
+                },
+            )
+            .unwrap();
+            grant_referrer(&mut app, USER3);
+            execute(
+                &mut app,
+                USER2,
+                ExecuteMsg::SetReferrer {
+                    referrer: USER3.into(),
+                },
+            )
+            .unwrap();
+
+            // 200 of fees, nobody referred the first contestant so its referrer share
+            // goes to the fee ledger
+            let game_room_id = create_gr(&mut app);
+            finish_gr(
+                &mut app,
+                game_room_id,
+                GameRoomStatus::Win { addr: USER1.into() },
+            );
+
+            assert_eq!(query_balance(&app, TREASURY), Uint128::new(100000000));
+            assert_eq!(query_balance(&app, JACKPOT), Uint128::new(40000000));
+            assert_eq!(query_balance(&app, USER3), Uint128::new(10000000));
+            let collected_fees: Uint128 = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::GetCollectedFees {})
+                .unwrap();
+            assert_eq!(collected_fees, Uint128::new(10000000));
+
+            let resp: ListFeeRecipientsResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::ListFeeRecipients {})
+                .unwrap();
+            assert_eq!(
+                resp.recipients
+                    .iter()
+                    .map(|recipient| recipient.accrued)
+                    .collect::<Vec<_>>(),
+                [100000000, 40000000, 40000000, 10000000].map(Uint128::new)
+            );
+            assert_eq!(resp.pending_burn, Uint128::new(40000000));
+
+            let err = execute(&mut app, USER1, ExecuteMsg::BurnFees {}).unwrap_err();
+            assert_err(
+                err,
+                ContractError::MissingRole {
+                    role: msg::Role::Treasurer,
+                },
+            );
+            execute(&mut app, ENIGMA_ADMIN, ExecuteMsg::BurnFees {}).unwrap();
+
+            let token_info: cw20::TokenInfoResponse = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.edt_addr.clone(), &test_edt::msg::QueryMsg::TokenInfo {})
+                .unwrap();
+            assert_eq!(token_info.total_supply, Uint128::new(19960000000));
+            let resp: ListFeeRecipientsResp = app
+                .app
+                .wrap()
+                .query_wasm_smart(app.enigma_addr.clone(), &QueryMsg::ListFeeRecipients {})
+                .unwrap();
+            assert_eq!(resp.pending_burn, Uint128::zero());
+        }
+    }
 }